@@ -2,11 +2,18 @@
 #![allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
 
 use std::{
+    collections::HashSet,
     io::{stdout, Stdout},
+    path::PathBuf,
     sync::mpsc::channel,
     time::Duration,
 };
-use tmt_core::{Component, ComponentType, Interface, Provider, TemperatureReading};
+#[cfg(feature = "replay")]
+use tmt_core::replay::{Playback, Recorder, ReplayComponents};
+use tmt_core::{
+    Component, ComponentType, ErrorKind, Interface, Provider, ReadingStatus, Temperature,
+    TemperatureReading, TemperatureUnit,
+};
 
 use ansi_to_tui::IntoText;
 use crossterm::{
@@ -16,6 +23,11 @@ use crossterm::{
     style::Stylize,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+#[cfg(unix)]
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM},
+    iterator::Signals,
+};
 use tui::{
     backend::CrosstermBackend as TuiBackend,
     layout::{Alignment, Constraint, Direction, Layout},
@@ -35,10 +47,28 @@ macro_rules! exit {
     }};
 }
 
+/// Where the components shown come from.
+#[derive(Clone)]
+enum ProviderChoice {
+    /// The sensors of this system.
+    System,
+    /// Synthetic components following the given scenario, or the built-in demo one.
+    #[cfg(feature = "mock")]
+    Mock(Option<PathBuf>),
+    /// The components of the given recording, played back as given.
+    #[cfg(feature = "replay")]
+    Replay(PathBuf, Playback),
+}
+
 #[allow(clippy::struct_excessive_bools, reason = "This is not a state machine")]
 struct Options {
+    provider: ProviderChoice,
+    /// The file to record every refresh to instead of showing the TUI, if any.
+    #[cfg(feature = "replay")]
+    record: Option<PathBuf>,
     interval: Duration,
-    critical: f64,
+    critical: Temperature,
+    unit: TemperatureUnit,
     no_raw_mode: bool,
     summary: bool,
     vertical: bool,
@@ -61,8 +91,39 @@ fn option_parser() -> getopts::Options {
     opts.optopt(
         "C",
         "critical",
-        "the critical temperature threshold in celsius",
-        "CELSIUS",
+        "the critical temperature threshold, in the unit temperatures are shown in",
+        "DEGREES",
+    );
+    opts.optopt(
+        "u",
+        "unit",
+        "the unit temperatures are shown in: C (default), F or K",
+        "UNIT",
+    );
+    opts.optopt(
+        "",
+        "provider",
+        "where components are read from: system (default), or mock if built with the mock feature",
+        "PROVIDER",
+    );
+    opts.optopt(
+        "",
+        "scenario",
+        "the TOML scenario of the mock provider, instead of the built-in demo",
+        "FILE",
+    );
+    #[cfg(feature = "replay")]
+    opts.optopt(
+        "",
+        "speed",
+        "how many times faster than real time a recording is replayed",
+        "FACTOR",
+    );
+    #[cfg(feature = "replay")]
+    opts.optflag(
+        "",
+        "step",
+        "replay one recorded refresh per interval instead of in real time",
     );
     opts
 }
@@ -78,7 +139,10 @@ fn parse_options() -> Result<Options, BoxError> {
         println!(
             "{}",
             opts.usage(
-                "Usage: tmt [options]\nRun without options to start TMT (then press ESC to exit).",
+                "Usage: tmt [options]\n       tmt record FILE [options]\n       \
+                 tmt replay FILE [options]\nRun without options to start TMT (then press ESC to \
+                 exit, or R to reset recorded peak temperatures). Record a session to FILE with \
+                 `record`, and play it back with `replay`.",
             )
         );
         exit!();
@@ -89,17 +153,98 @@ fn parse_options() -> Result<Options, BoxError> {
         exit!();
     }
 
+    let unit = matches
+        .opt_str("u")
+        .map_or(TemperatureUnit::Celsius, |unit| {
+            unit.parse().unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                exit!(2);
+            })
+        });
+    let critical = match matches.opt_str("C") {
+        Some(critical) => Temperature::from_unit(critical.parse::<f64>()?, unit),
+        None => Temperature::from_millidegrees(90_000),
+    };
+
+    let mut command = matches.free.iter().map(String::as_str);
+    let (record, replay) = match (command.next(), command.next(), command.next()) {
+        (None, _, _) => (None, None),
+        (Some("record"), Some(path), None) => (Some(PathBuf::from(path)), None),
+        (Some("replay"), Some(path), None) => (None, Some(PathBuf::from(path))),
+        (Some(command @ ("record" | "replay")), None, _) => {
+            eprintln!("error: {} requires a FILE", command);
+            exit!(2);
+        }
+        (Some(command), _, _) => {
+            eprintln!("error: unexpected argument {:?}", command);
+            exit!(2);
+        }
+    };
+
+    #[cfg(not(feature = "replay"))]
+    if record.is_some() || replay.is_some() {
+        eprintln!("error: this build of tmt does not include recording");
+        exit!(2);
+    }
+
+    #[cfg(feature = "replay")]
+    if replay.is_none() && (matches.opt_present("speed") || matches.opt_present("step")) {
+        eprintln!("error: --speed and --step require replay");
+        exit!(2);
+    }
+
+    let scenario = matches.opt_str("scenario").map(PathBuf::from);
+    let provider = match matches.opt_str("provider").as_deref() {
+        #[cfg(feature = "replay")]
+        None if scenario.is_none() && replay.is_some() => {
+            let playback = if matches.opt_present("step") {
+                Playback::Stepped
+            } else {
+                let speed = matches
+                    .opt_str("speed")
+                    .map_or(Ok(1.0), |s| s.parse::<f64>())?;
+                if !speed.is_finite() || speed <= 0.0 {
+                    eprintln!("error: --speed must be a positive number, got {}", speed);
+                    exit!(2);
+                }
+                Playback::Speed(speed)
+            };
+            ProviderChoice::Replay(replay.unwrap(), playback)
+        }
+        _ if replay.is_some() => {
+            eprintln!("error: replay can't be combined with --provider or --scenario");
+            exit!(2);
+        }
+        None | Some("system") if scenario.is_none() => ProviderChoice::System,
+        #[cfg(feature = "mock")]
+        Some("mock") => ProviderChoice::Mock(scenario),
+        #[cfg(not(feature = "mock"))]
+        Some("mock") => {
+            eprintln!("error: this build of tmt does not include the mock provider");
+            exit!(2);
+        }
+        None | Some("system") => {
+            eprintln!("error: --scenario requires --provider mock");
+            exit!(2);
+        }
+        Some(provider) => {
+            eprintln!("error: unknown provider {:?}", provider);
+            exit!(2);
+        }
+    };
+
     Ok(Options {
+        provider,
+        #[cfg(feature = "replay")]
+        record,
         interval: Duration::from_secs_f64(
             matches
                 .opt_str("i")
                 .unwrap_or_else(|| "2.0".to_string())
                 .parse::<f64>()?,
         ),
-        critical: matches
-            .opt_str("C")
-            .unwrap_or_else(|| "90.0".to_string())
-            .parse::<f64>()?,
+        critical,
+        unit,
         no_raw_mode: matches.opt_present("N"),
         summary: matches.opt_present("s"),
         vertical: matches.opt_present("vertical"),
@@ -110,6 +255,24 @@ type Backend = TuiBackend<Stdout>;
 
 const HEADER: &str = concat!("TMT v", env!("CARGO_PKG_VERSION"));
 
+/// The panels shown in the UI, in order. Panels without any components are hidden.
+const PANELS: [(ComponentType, &str); 11] = [
+    (ComponentType::Cpu, "CPUs"),
+    (ComponentType::Gpu, "GPUs"),
+    (ComponentType::Memory, "Memory"),
+    (ComponentType::Storage, "Storage"),
+    (ComponentType::Motherboard, "Motherboard"),
+    (ComponentType::Chipset, "Chipset"),
+    (ComponentType::Network, "Network"),
+    (ComponentType::Battery, "Battery"),
+    (ComponentType::Psu, "PSU"),
+    (ComponentType::System, "System Components"),
+    (ComponentType::Sensor, "Sensors"),
+];
+
+/// The maximum number of panels shown side by side before wrapping onto another row.
+const PANELS_PER_ROW: usize = 3;
+
 macro_rules! key_value_ui {
     ($k:expr, $v:expr) => {{
         format!("{}{} {}\n", $k.bold().white(), ":".bold().white(), $v)
@@ -121,25 +284,29 @@ impl TemperatureReading for &Options {
         unreachable!("options.label() should not be used in the UI")
     }
 
-    fn temperature(&self) -> f64 {
+    fn temperature(&self) -> Temperature {
         unreachable!("options.temperature() should not be used in the UI")
     }
 
-    fn max(&self) -> f64 {
+    fn max(&self) -> Temperature {
         unreachable!("options.max() should not be used in the UI")
     }
 
-    fn high(&self) -> f64 {
-        self.critical - 15.0
+    fn high(&self) -> Temperature {
+        Temperature::from_millidegrees(self.critical.millidegrees() - 15_000)
     }
 
-    fn critical(&self) -> f64 {
+    fn critical(&self) -> Temperature {
         self.critical
     }
 }
 
-fn format_thermal_intensity(temp: f64, options: impl TemperatureReading) -> String {
-    let mut reading = format!("{:.1}° C", temp);
+fn format_thermal_intensity(
+    temp: Temperature,
+    options: impl TemperatureReading,
+    unit: TemperatureUnit,
+) -> String {
+    let mut reading = format!("{:.1}", temp.display(unit));
     if temp >= options.critical() {
         reading = reading.red().bold().to_string();
         reading.push_str(" (CRITICAL)");
@@ -155,9 +322,9 @@ fn format_thermal_intensity(temp: f64, options: impl TemperatureReading) -> Stri
 fn render_xpu<'a>(
     component_type: ComponentType,
     title: &'static str,
-    name: String,
+    name: Option<String>,
     show_all: bool,
-    provider: &mut Provider,
+    provider: &impl Interface,
     options: &'a Options,
 ) -> Option<Paragraph<'a>> {
     let components = provider.thermal_components_by_type(component_type);
@@ -167,47 +334,137 @@ fn render_xpu<'a>(
 
     let mut cpus_content = String::new();
     let (mut sum, mut total) = (0.0, 0_usize);
-    let mut max = ("Unknown".to_string(), 0.0);
+    let mut max: Option<(String, Temperature)> = None;
+
+    // Group CPU readings by package and die, keeping every other reading in its original order.
+    let mut readings = components
+        .iter()
+        .flat_map(|component| component.temperatures())
+        .collect::<Vec<_>>();
+    readings.sort_by_key(|reading| {
+        reading
+            .cpu_location()
+            .map(|location| (location.package, location.die, location.core))
+    });
 
-    for cpu in components.iter() {
-        let temps = cpu.temperatures();
+    let groups = readings
+        .iter()
+        .filter_map(TemperatureReading::cpu_location)
+        .map(|location| (location.package, location.die))
+        .collect::<HashSet<_>>();
+    let mut group = None;
 
-        for reading in temps {
-            let temp = reading.temperature();
-            sum += temp;
+    for reading in readings {
+        let temp = reading.temperature();
+        let status = reading.status();
+
+        // Unavailable readings have no value, so they don't count towards the statistics.
+        if status.has_value() {
+            sum += temp.celsius();
             total += 1;
 
-            if temp > max.1 {
-                max = (reading.label(), temp);
+            let hottest = match &max {
+                Some((_, max)) => temp > *max,
+                None => true,
+            };
+            if hottest {
+                max = Some((reading.label(), temp));
             }
+        }
 
-            if show_all {
-                cpus_content.push_str(&key_value_ui!(
-                    reading.label(),
-                    format_thermal_intensity(temp, reading)
-                ));
+        if show_all {
+            let location = reading.cpu_location();
+            let key = location
+                .as_ref()
+                .map(|location| (location.package, location.die));
+
+            if groups.len() > 1 && key != group {
+                if let Some(location) = location {
+                    let heading = match location.die {
+                        Some(die) => format!("Package {} / CCD {}", location.package, die),
+                        None => format!("Package {}", location.package),
+                    };
+                    cpus_content.push_str(&format!("{}\n", heading.bold().magenta()));
+                }
+                group = key;
             }
+
+            let label = reading.label();
+            let usage = reading
+                .usage()
+                .map_or_else(String::new, |usage| format!(" ({:.0}% load)", usage));
+            let alarms = reading.alarms();
+            let alarm = if alarms.is_fault() {
+                format!(" {}", "(FAULT)".bold().red())
+            } else if !alarms.is_empty() {
+                format!(" {}", "(ALARM)".bold().on_red())
+            } else {
+                String::new()
+            };
+            let usage = usage + &alarm;
+            let value = match status {
+                ReadingStatus::Ok => format_thermal_intensity(temp, reading, options.unit) + &usage,
+                ReadingStatus::Stale(err) => format!(
+                    "{}{} {}",
+                    format_thermal_intensity(temp, reading, options.unit),
+                    usage,
+                    format!("(stale: {})", err).yellow()
+                ),
+                ReadingStatus::Sleeping => format!(
+                    "{}{} {}",
+                    format_thermal_intensity(temp, reading, options.unit),
+                    usage,
+                    "(sleeping)".dark_grey()
+                ),
+                ReadingStatus::Unavailable(err) => {
+                    format!("N/A ({})", err).bold().red().to_string() + &alarm
+                }
+            };
+            cpus_content.push_str(&key_value_ui!(label, value));
         }
     }
 
-    let average = sum / total as f64;
-    let mut cpus = format!("{} {}\n", "Name:".bold().cyan(), name.bold().white());
+    let average = if total > 0 {
+        format_thermal_intensity(
+            Temperature::from_celsius(sum / total as f64),
+            options,
+            options.unit,
+        )
+    } else {
+        "N/A".bold().white().to_string()
+    };
+    let mut cpus = name.map_or_else(String::new, |name| {
+        format!("{} {}\n", "Name:".bold().cyan(), name.bold().white())
+    });
     cpus.push_str(&format!(
         "{} {}\n",
         "Count:".bold().cyan(),
         total.to_string().bold().white(),
     ));
-    cpus.push_str(&format!(
-        "{} {}\n",
-        "Average:".bold().cyan(),
-        format_thermal_intensity(average, options)
-    ));
-    cpus.push_str(&format!(
-        "{} {} ({})\n",
-        "Hottest:".cyan().bold(),
-        max.0.white().bold(),
-        format_thermal_intensity(max.1, options),
-    ));
+
+    // Show load alongside heat, e.g. the utilisation of the CPUs the components measure.
+    let usages = components
+        .iter()
+        .filter_map(|component| component.percentage())
+        .collect::<Vec<_>>();
+    if !usages.is_empty() {
+        let usage = usages.iter().sum::<f32>() / usages.len() as f32;
+        cpus.push_str(&format!(
+            "{} {}\n",
+            "Usage:".bold().cyan(),
+            format!("{:.0}%", usage).bold().white(),
+        ));
+    }
+
+    cpus.push_str(&format!("{} {}\n", "Average:".bold().cyan(), average));
+    if let Some((label, temp)) = max {
+        cpus.push_str(&format!(
+            "{} {} ({})\n",
+            "Hottest:".cyan().bold(),
+            label.white().bold(),
+            format_thermal_intensity(temp, options, options.unit),
+        ));
+    }
     cpus.push_str(&cpus_content);
 
     Some(
@@ -222,12 +479,128 @@ fn render_xpu<'a>(
     )
 }
 
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+fn render_battery(provider: &impl Interface, unit: TemperatureUnit) -> Option<Paragraph<'static>> {
+    let batteries = provider.battery_components();
+    if batteries.is_empty() {
+        return None;
+    }
+
+    let mut content = String::new();
+
+    for battery in batteries {
+        let state = battery.battery()?;
+        content.push_str(&format!("{}\n", battery.label().bold().magenta()));
+
+        let charge = battery
+            .percentage()
+            .map_or_else(|| "N/A".to_string(), |p| format!("{:.0}%", p));
+        content.push_str(&key_value_ui!(
+            "Charge",
+            format!("{} ({})", charge, state.status).bold().white()
+        ));
+
+        if let (Some(energy), Some(full)) = (state.energy, state.energy_full) {
+            content.push_str(&key_value_ui!(
+                "Energy",
+                format!("{:.1} / {:.1} Wh", energy, full).bold().white()
+            ));
+        }
+
+        if let Some(power) = state.power {
+            content.push_str(&key_value_ui!(
+                "Power",
+                format!("{:.1} W", power).bold().white()
+            ));
+        }
+
+        if let Some(time) = state.time_to_empty {
+            content.push_str(&key_value_ui!(
+                "Time left",
+                format_duration(time).bold().white()
+            ));
+        } else if let Some(time) = state.time_to_full {
+            content.push_str(&key_value_ui!(
+                "Time to full",
+                format_duration(time).bold().white()
+            ));
+        }
+
+        for reading in battery.temperatures() {
+            content.push_str(&key_value_ui!(
+                "Temperature",
+                format_thermal_intensity(reading.temperature(), reading, unit)
+            ));
+        }
+    }
+
+    Some(
+        Paragraph::new(content.into_text().unwrap())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Battery")
+                    .border_style(Style::default().fg(Color::Gray)),
+            )
+            .wrap(Wrap { trim: false }),
+    )
+}
+
+fn render_fans(provider: &impl Interface) -> Option<Paragraph<'static>> {
+    let fans = provider.fan_components();
+    if fans.is_empty() {
+        return None;
+    }
+
+    let mut content = format!(
+        "{} {}\n",
+        "Count:".bold().cyan(),
+        fans.len().to_string().bold().white(),
+    );
+
+    for fan in fans {
+        let mut speed = fan
+            .rpm()
+            .map_or_else(|| "N/A".to_string(), |rpm| format!("{:.0} RPM", rpm));
+
+        if let Some(percentage) = fan.percentage() {
+            speed.push_str(&format!(" ({:.0}%)", percentage));
+        }
+
+        content.push_str(&key_value_ui!(fan.label(), speed.bold().white()));
+    }
+
+    Some(
+        Paragraph::new(content.into_text().unwrap())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Fans")
+                    .border_style(Style::default().fg(Color::Gray)),
+            )
+            .wrap(Wrap { trim: false }),
+    )
+}
+
 fn render(
     terminal: &mut Terminal<Backend>,
-    provider: &mut Provider,
+    provider: &mut impl Interface,
     options: &Options,
+    reset_history: bool,
 ) -> Result<(), BoxError> {
-    provider.refresh()?;
+    let reset = if reset_history {
+        provider.reset_history()
+    } else {
+        Ok(())
+    };
+
+    // A component failing to refresh is shown rather than quitting, as every other component still
+    // has up-to-date readings.
+    let refreshed = provider.refresh();
 
     terminal.set_cursor(0, 0)?;
     terminal.draw(|frame| {
@@ -247,6 +620,48 @@ fn render(
         system.push_str(&key_value_ui!("Operating System", provider.os_name()));
         system.push_str(&key_value_ui!("Device", provider.device_model_name()));
 
+        if let Some(kernel) = provider.kernel_version() {
+            system.push_str(&key_value_ui!("Kernel", kernel));
+        }
+
+        let board = [provider.board_vendor(), provider.board_name()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        if !board.is_empty() {
+            system.push_str(&key_value_ui!("Board", board.join(" ")));
+        }
+
+        if let Some(bios) = provider.bios_version() {
+            system.push_str(&key_value_ui!("BIOS", bios));
+        }
+
+        match (provider.core_count(), provider.thread_count()) {
+            (Some(cores), Some(threads)) => system.push_str(&key_value_ui!(
+                "Processor",
+                format!("{} cores, {} threads", cores, threads)
+            )),
+            (None, Some(threads)) => {
+                system.push_str(&key_value_ui!("Processor", format!("{} threads", threads)));
+            }
+            _ => (),
+        }
+
+        for err in [&reset, &refreshed]
+            .into_iter()
+            .filter_map(|r| r.as_ref().err())
+        {
+            // Permission problems are worth acting on, unlike a sensor that failed to read once.
+            let hint = match err.kind() {
+                ErrorKind::PermissionDenied => " (try running as root)",
+                _ => "",
+            };
+            system.push_str(&key_value_ui!(
+                "Error",
+                format!("{}{}", err, hint).bold().red()
+            ));
+        }
+
         let system = Paragraph::new(system.into_text().unwrap()).block(
             Block::default()
                 .borders(Borders::ALL)
@@ -254,33 +669,38 @@ fn render(
                 .border_style(Style::default().fg(Color::Gray)),
         );
 
-        let entries = [
-            render_xpu(
-                ComponentType::Cpu,
-                "CPUs",
-                provider.cpu_name(),
-                !options.summary,
-                provider,
-                options,
-            ),
-            render_xpu(
-                ComponentType::Gpu,
-                "GPUs",
-                "N/A".to_string(),
-                !options.summary,
-                provider,
-                options,
-            ),
-        ]
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
+        let entries = PANELS
+            .into_iter()
+            .filter_map(|(component_type, title)| {
+                // Batteries with charge information get a dedicated panel.
+                if component_type == ComponentType::Battery
+                    && !provider.battery_components().is_empty()
+                {
+                    return render_battery(provider, options.unit);
+                }
 
-        let constraints = if entries.is_empty() {
-            Vec::with_capacity(0)
-        } else {
-            vec![Constraint::Percentage(100 / entries.len() as u16); entries.len()]
-        };
+                let name = match component_type {
+                    ComponentType::Cpu => Some(provider.cpu_name()),
+                    ComponentType::Gpu => {
+                        Some(provider.gpu_name().unwrap_or_else(|| "N/A".to_string()))
+                    }
+                    _ => None,
+                };
+
+                render_xpu(
+                    component_type,
+                    title,
+                    name,
+                    !options.summary,
+                    provider,
+                    options,
+                )
+            })
+            .chain(render_fans(provider))
+            .collect::<Vec<_>>();
+
+        let rows = entries.chunks(PANELS_PER_ROW).count();
+        let row_constraints = vec![Constraint::Percentage(100 / rows.max(1) as u16); rows];
 
         let layout = Layout::default()
             .direction(Direction::Vertical)
@@ -288,29 +708,165 @@ fn render(
             .margin(1)
             .split(full);
 
-        let next_row = Layout::default()
-            .direction(if options.vertical {
-                Direction::Vertical
-            } else {
-                Direction::Horizontal
-            })
-            .constraints(constraints)
-            .split(layout[1]);
+        let next_rows = if options.vertical {
+            vec![layout[1]]
+        } else {
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(row_constraints)
+                .split(layout[1])
+        };
 
         frame.render_widget(block, full);
         frame.render_widget(system, layout[0]);
 
-        for (i, entry) in entries.into_iter().enumerate() {
-            frame.render_widget(entry, next_row[i]);
+        let per_row = if options.vertical {
+            entries.len().max(1)
+        } else {
+            PANELS_PER_ROW
+        };
+        let mut entries = entries.into_iter();
+
+        for row in next_rows {
+            let entries = entries.by_ref().take(per_row).collect::<Vec<_>>();
+            let constraints = if entries.is_empty() {
+                Vec::with_capacity(0)
+            } else {
+                vec![Constraint::Percentage(100 / entries.len() as u16); entries.len()]
+            };
+
+            let next_row = Layout::default()
+                .direction(if options.vertical {
+                    Direction::Vertical
+                } else {
+                    Direction::Horizontal
+                })
+                .constraints(constraints)
+                .split(row);
+
+            for (i, entry) in entries.into_iter().enumerate() {
+                frame.render_widget(entry, next_row[i]);
+            }
         }
     })?;
 
     Ok(())
 }
 
+/// Discovers sensors, describing what to do about it if that fails or finds nothing.
+fn init_provider() -> Result<Provider, String> {
+    let provider = Provider::new().map_err(|err| {
+        let hint = match err.kind() {
+            ErrorKind::PermissionDenied => "; try running as root",
+            ErrorKind::NotFound | ErrorKind::Unsupported => "; running in a VM?",
+            _ => "",
+        };
+
+        format!("could not access sensors: {}{}", err, hint)
+    })?;
+
+    let has_sensors = !provider.thermal_components().is_empty()
+        || !provider.fan_components().is_empty()
+        || !provider.battery_components().is_empty()
+        || !provider.sensor_components().is_empty();
+
+    if has_sensors {
+        Ok(provider)
+    } else {
+        Err("no sensors found; are coretemp/k10temp loaded? running in a VM?".to_string())
+    }
+}
+
 fn main() -> Result<(), BoxError> {
     let options = parse_options()?;
 
+    // This happens before the TUI takes over the terminal, so the message stays visible.
+    match options.provider.clone() {
+        ProviderChoice::System => {
+            let provider = init_provider().unwrap_or_else(|err| {
+                eprintln!("error: {}", err);
+                exit!(1);
+            });
+            start(provider, options)
+        }
+        #[cfg(feature = "mock")]
+        ProviderChoice::Mock(scenario) => {
+            let provider = scenario
+                .map_or_else(
+                    || Ok(tmt_core::mock::MockComponents::default()),
+                    tmt_core::mock::MockComponents::load,
+                )
+                .unwrap_or_else(|err| {
+                    eprintln!("error: could not load scenario: {}", err);
+                    exit!(1);
+                });
+            start(provider, options)
+        }
+        #[cfg(feature = "replay")]
+        ProviderChoice::Replay(path, playback) => {
+            let provider = ReplayComponents::load(path, playback).unwrap_or_else(|err| {
+                eprintln!("error: could not load recording: {}", err);
+                exit!(1);
+            });
+            start(provider, options)
+        }
+    }
+}
+
+/// Records the given provider if asked to, otherwise shows it in the TUI.
+fn start(provider: impl Interface + Send, options: Options) -> Result<(), BoxError> {
+    #[cfg(feature = "replay")]
+    if let Some(path) = &options.record {
+        return record(provider, path, options.interval);
+    }
+
+    run(provider, options)
+}
+
+/// Records every refresh of the given provider to a file, until TMT is interrupted.
+#[cfg(feature = "replay")]
+fn record(
+    mut provider: impl Interface,
+    path: &std::path::Path,
+    interval: Duration,
+) -> Result<(), BoxError> {
+    let mut recorder = Recorder::create(path)?;
+
+    #[cfg(unix)]
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP])?;
+    #[cfg(unix)]
+    let mut interrupted = move || signals.pending().next().is_some();
+    // The recording is flushed after every refresh, so it stays valid when TMT is killed instead.
+    #[cfg(not(unix))]
+    let mut interrupted = || false;
+
+    eprintln!("recording to {}, press Ctrl+C to stop", path.display());
+    let mut refreshes = 0_usize;
+
+    while !interrupted() {
+        // Readings that failed to refresh are recorded as stale, like the TUI shows them.
+        if let Err(err) = provider.refresh() {
+            eprintln!("warning: {}", err);
+        }
+
+        recorder.record(&provider.snapshot())?;
+        refreshes += 1;
+        std::thread::sleep(interval);
+    }
+
+    eprintln!("recorded {} refreshes to {}", refreshes, path.display());
+    Ok(())
+}
+
+/// Runs the TUI until it is exited, showing the components of the given provider.
+fn run(provider: impl Interface + Send, options: Options) -> Result<(), BoxError> {
+    // Never leave a fan pinned at a manual speed, even if TMT crashes.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = tmt_core::restore_fans();
+        default_hook(info);
+    }));
+
     let mut out = stdout();
     execute!(out, EnterAlternateScreen, EnableMouseCapture)?;
     if !options.no_raw_mode {
@@ -319,20 +875,26 @@ fn main() -> Result<(), BoxError> {
 
     let backend = TuiBackend::new(out);
     let mut terminal = Terminal::new(backend)?;
-    let provider = Provider::default();
 
     let (tx, rx) = channel();
+    let (reset_tx, reset_rx) = channel();
     let esc_tx = tx.clone();
+    #[cfg(unix)]
+    let signal_tx = tx.clone();
+    #[cfg(unix)]
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP])?;
     let terminal = &mut terminal;
 
     std::thread::scope(|s| {
         s.spawn(|| {
             let tx = tx;
+            let reset_rx = reset_rx;
             let mut provider = provider;
             let options = options;
 
             loop {
-                render(terminal, &mut provider, &options).unwrap_or_else(|err| {
+                let reset_history = reset_rx.try_iter().count() > 0;
+                render(terminal, &mut provider, &options, reset_history).unwrap_or_else(|err| {
                     eprintln!("Error occured while rendering: {}", err);
                     tx.send(()).unwrap();
                 });
@@ -346,13 +908,29 @@ fn main() -> Result<(), BoxError> {
                         && key.modifiers.contains(KeyModifiers::CONTROL)
                 {
                     esc_tx.send(()).unwrap();
+                } else if key.code == KeyCode::Char('r') {
+                    reset_tx.send(()).unwrap();
                 }
             }
         });
+        #[cfg(unix)]
+        s.spawn(move || {
+            if signals.forever().next().is_some() {
+                signal_tx.send(()).unwrap();
+            }
+        });
         s.spawn(move || {
             rx.recv().unwrap();
             disable_raw_mode().unwrap();
             execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture, Show).unwrap();
+
+            if let Err(err) = tmt_core::restore_fans() {
+                eprintln!(
+                    "error: could not restore fans to automatic control: {}",
+                    err
+                );
+                exit!(1);
+            }
             exit!();
         });
     });
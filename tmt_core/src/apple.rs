@@ -1,6 +1,11 @@
 //! Uses Apple's SMC sensors to get data.
 
-use crate::{smc, Component, ComponentType, Interface, TemperatureReading};
+use std::collections::HashMap;
+
+use crate::{
+    smc, Component, ComponentType, Error, ErrorKind, Interface, SensorId, SensorKind, Temperature,
+    TemperatureReading,
+};
 
 bitflags::bitflags! {
     /// Represents a platform compatible with a sensor.
@@ -37,6 +42,29 @@ impl From<smc::SmcError> for AppleError {
     }
 }
 
+impl From<smc::SmcError> for Error {
+    fn from(err: smc::SmcError) -> Self {
+        let kind = match err {
+            smc::SmcError::DriverNotFound | smc::SmcError::UnsafeFanSpeed => ErrorKind::Unsupported,
+            smc::SmcError::KeyNotFound(_) => ErrorKind::NotFound,
+            smc::SmcError::NotPrivileged => ErrorKind::PermissionDenied,
+            smc::SmcError::FailedToOpen | smc::SmcError::Unknown(..) | smc::SmcError::Sysctl(_) => {
+                ErrorKind::Transient
+            }
+        };
+
+        Self::new(kind, err)
+    }
+}
+
+impl From<AppleError> for Error {
+    fn from(err: AppleError) -> Self {
+        match err {
+            AppleError::Smc(err) => err.into(),
+        }
+    }
+}
+
 /// Represents a common group of sensors.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum SensorGroup {
@@ -50,23 +78,6 @@ pub enum SensorGroup {
     System,
 }
 
-/// Represents a type of data that a sensor can return.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum SensorKind {
-    /// Measures thermal data.
-    Temperature,
-    /// Measures voltage.
-    Voltage,
-    /// Measures current.
-    Current,
-    /// Measures power.
-    Power,
-    /// Measures fan speed.
-    Fan,
-    /// Measures energy consumption.
-    Energy,
-}
-
 /// Represents a detectable sensor.
 #[derive(Copy, Clone, Debug)]
 pub struct Sensor {
@@ -123,29 +134,38 @@ impl Sensor {
         self.component_type = kind;
         self
     }
+
+    /// The stable ID of this sensor, built from its SMC key, e.g. `smc:TC0P`.
+    fn id(&self) -> SensorId {
+        SensorId::new(format!("smc:{}", self.key))
+    }
 }
 
-pub struct AppleTemperatureReading(String, f64, f64);
+pub struct AppleTemperatureReading(String, f64, f64, SensorId);
 
 impl TemperatureReading for AppleTemperatureReading {
     fn label(&self) -> String {
         self.0.clone()
     }
 
-    fn temperature(&self) -> f64 {
-        self.1
+    fn id(&self) -> SensorId {
+        self.3.clone()
     }
 
-    fn max(&self) -> f64 {
-        self.2
+    fn temperature(&self) -> Temperature {
+        Temperature::from_celsius(self.1)
     }
 
-    fn high(&self) -> f64 {
-        85.0
+    fn max(&self) -> Temperature {
+        Temperature::from_celsius(self.2)
     }
 
-    fn critical(&self) -> f64 {
-        100.0
+    fn high(&self) -> Temperature {
+        Temperature::from_millidegrees(85_000)
+    }
+
+    fn critical(&self) -> Temperature {
+        Temperature::from_millidegrees(100_000)
     }
 }
 
@@ -168,6 +188,14 @@ macro_rules! apple_component {
                }
             }
 
+            fn id(&self) -> SensorId {
+               match self {
+                   $(
+                       Self::$variant(component) => component.id(),
+                   )+
+               }
+            }
+
             fn temperatures(&self) -> Vec<Self::TemperatureReading> {
                 match self {
                     $(
@@ -184,7 +212,7 @@ macro_rules! apple_component {
                 }
             }
 
-            fn refresh(&mut self) -> Result<(), String> {
+            fn refresh(&mut self) -> Result<(), Error> {
                 match self {
                     $(
                         Self::$variant(component) => component.refresh(),
@@ -217,9 +245,13 @@ macro_rules! xpu_component_impl {
                     self.inner.name.to_string()
                 }
 
+                fn id(&self) -> SensorId {
+                    self.inner.id()
+                }
+
                 fn temperatures(&self) -> Vec<Self::TemperatureReading> {
                     vec![
-                        AppleTemperatureReading(self.label(), self.previous, self.max)
+                        AppleTemperatureReading(self.label(), self.previous, self.max, self.id())
                     ]
                 }
 
@@ -227,11 +259,11 @@ macro_rules! xpu_component_impl {
                     self.inner.component_type
                 }
 
-                fn refresh(&mut self) -> Result<(), String> {
+                fn refresh(&mut self) -> Result<(), Error> {
                     self.previous = self
                         .smc
                         .temperature(self.inner.key.into())
-                        .map_err(|e| e.to_string())?;
+                        .map_err(|err| Error::from(err).with_component(self.id()))?;
                     self.max = self.max.max(self.previous);
 
                     Ok(())
@@ -246,10 +278,13 @@ xpu_component_impl!(AppleCpuComponent AppleGpuComponent);
 pub struct AppleComponents {
     smc: smc::Smc,
     sensors: Vec<(Sensor, AppleComponent)>,
+    /// The position of every sensor in `sensors` by its ID.
+    index: HashMap<SensorId, usize>,
 }
 
 impl AppleComponents {
-    fn new() -> Result<Self, AppleError> {
+    /// Opens the SMC and discovers the sensors this Mac has. Opening the SMC fails without root.
+    pub fn new() -> Result<Self, Error> {
         let smc = smc::Smc::new()?;
         let keys = smc.keys()?;
         let platform = read_platform();
@@ -278,9 +313,18 @@ impl AppleComponents {
                     None
                 }
             })
+            .collect::<Vec<_>>();
+        let index = sensors
+            .iter()
+            .enumerate()
+            .map(|(i, (sensor, _))| (sensor.id(), i))
             .collect();
 
-        Ok(Self { smc, sensors })
+        Ok(Self {
+            smc,
+            sensors,
+            index,
+        })
     }
 }
 
@@ -342,6 +386,7 @@ lazy_static::lazy_static! {
         // Silicon processors, this should be enough: Apple MXX XXXXXXXXXXXXXXX
         read_sysctl::<24>("machdep.cpu.brand_string").unwrap_or_else(|| "Unknown".to_string())
     };
+    static ref KERNEL_VERSION: Option<String> = unsafe { read_sysctl::<32>("kern.osrelease") };
     static ref MODEL_NAME: String = unsafe {
         read_mac_model().map(|(mac_type, details)| format!("{} ({})", mac_type.as_str(), details))
             .unwrap_or_else(|| "Unknown".to_string())
@@ -365,6 +410,13 @@ impl Interface for AppleComponents {
             .collect()
     }
 
+    fn component_by_id(&self, id: &SensorId) -> Option<&Self::Component> {
+        self.index
+            .get(id)
+            .or_else(|| self.index.get(&id.component()))
+            .map(|&i| &self.sensors[i].1)
+    }
+
     fn os_name(&self) -> String {
         OS_NAME.clone()
     }
@@ -376,8 +428,13 @@ impl Interface for AppleComponents {
     fn device_model_name(&self) -> String {
         MODEL_NAME.clone()
     }
+
+    fn kernel_version(&self) -> Option<String> {
+        KERNEL_VERSION.clone()
+    }
 }
 
+/// Discovers sensors like [`AppleComponents::new`], panicking if that fails.
 impl Default for AppleComponents {
     fn default() -> Self {
         Self::new().expect("could not init SMC: are you running as root?")
@@ -688,7 +745,8 @@ pub const SENSORS: [Sensor; 94] = [
         Platform::INTEL,
     ),
     Sensor::system("TB1T", "Battery", SensorKind::Temperature, Platform::INTEL),
-    Sensor::system("TW0P", "Airport", SensorKind::Temperature, Platform::all()),
+    Sensor::system("TW0P", "Airport", SensorKind::Temperature, Platform::all())
+        .component_type(ComponentType::Network),
     Sensor::system("TL0P", "Display", SensorKind::Temperature, Platform::all()),
     Sensor::system(
         "TI%P",
@@ -701,19 +759,22 @@ pub const SENSORS: [Sensor; 94] = [
         "Disk % (A)",
         SensorKind::Temperature,
         Platform::all(),
-    ),
+    )
+    .component_type(ComponentType::Storage),
     Sensor::system(
         "TH%B",
         "Disk % (B)",
         SensorKind::Temperature,
         Platform::all(),
-    ),
+    )
+    .component_type(ComponentType::Storage),
     Sensor::system(
         "TH%C",
         "Disk % (C)",
         SensorKind::Temperature,
         Platform::all(),
-    ),
+    )
+    .component_type(ComponentType::Storage),
     Sensor::system(
         "TN0D",
         "Northbridge diode",
@@ -925,7 +986,8 @@ pub const SENSORS: [Sensor; 94] = [
         "NAND",
         SensorKind::Temperature,
         Platform::APPLE_SILICON,
-    ),
+    )
+    .component_type(ComponentType::Storage),
     Sensor::system(
         "TB1T",
         "Battery 1",
@@ -945,7 +1007,8 @@ pub const SENSORS: [Sensor; 94] = [
         "Airport",
         SensorKind::Temperature,
         Platform::APPLE_SILICON,
-    ),
+    )
+    .component_type(ComponentType::Network),
     // Voltage sensors
     Sensor::system("VCAC", "CPU IA", SensorKind::Voltage, Platform::all()),
     Sensor::system(
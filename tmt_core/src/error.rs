@@ -0,0 +1,124 @@
+//! The error type returned by [`Component`](crate::Component) and [`Interface`](crate::Interface).
+
+use std::fmt;
+
+use crate::SensorId;
+
+/// What went wrong, which tells applications how to react to an [`Error`]: retry it, hide the
+/// sensor, or ask for root.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorKind {
+    /// The operation requires more privileges, e.g. controlling a fan without root.
+    PermissionDenied,
+    /// The sensor or device does not exist, e.g. because it was unplugged.
+    NotFound,
+    /// The operation failed but may succeed if retried, e.g. because of a bus error.
+    Transient,
+    /// The hardware or the OS returned data that could not be understood.
+    InvalidData,
+    /// The operation is not supported by the hardware or the platform, or was refused, e.g.
+    /// setting a fan to an unsafe speed.
+    Unsupported,
+}
+
+impl ErrorKind {
+    /// A short description of this kind, e.g. `permission denied`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PermissionDenied => "permission denied",
+            Self::NotFound => "not found",
+            Self::Transient => "transient failure",
+            Self::InvalidData => "invalid data",
+            Self::Unsupported => "unsupported",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error raised while reading or controlling sensors, categorised by its [`ErrorKind`], along
+/// with the ID of the component it originated from, if any, and the platform's own error as its
+/// [`source`](std::error::Error::source).
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    component: Option<SensorId>,
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl Error {
+    /// Creates an error of the given kind, caused by the given platform error or message.
+    pub fn new(
+        kind: ErrorKind,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self {
+            kind,
+            component: None,
+            source: source.into(),
+        }
+    }
+
+    /// Attributes this error to the component with the given ID.
+    #[must_use]
+    pub fn with_component(mut self, id: SensorId) -> Self {
+        self.component = Some(id);
+        self
+    }
+
+    /// What went wrong.
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The ID of the component this error originated from, if it is specific to one.
+    #[must_use]
+    pub const fn component(&self) -> Option<&SensorId> {
+        self.component.as_ref()
+    }
+
+    /// Whether the operation may succeed if retried as it is.
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        self.kind == ErrorKind::Transient
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.component {
+            Some(id) => write!(f, "{}: {}", id, self.source),
+            None => write!(f, "{}", self.source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+impl From<std::io::ErrorKind> for ErrorKind {
+    fn from(kind: std::io::ErrorKind) -> Self {
+        match kind {
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            std::io::ErrorKind::NotFound => Self::NotFound,
+            std::io::ErrorKind::InvalidData => Self::InvalidData,
+            std::io::ErrorKind::Unsupported => Self::Unsupported,
+            _ => Self::Transient,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::new(err.kind().into(), err)
+    }
+}
@@ -12,13 +12,25 @@
 
 #[cfg(target_os = "macos")]
 mod apple;
+mod error;
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "replay")]
+pub mod replay;
 #[cfg(target_os = "macos")]
 pub(crate) mod smc;
-#[cfg(target_os = "linux")]
-mod linux;
+mod snapshot;
+mod temperature;
+
+pub use error::{Error, ErrorKind};
+pub use snapshot::{ComponentSnapshot, Snapshot, SystemInfo, TemperatureSnapshot};
+pub use temperature::{DisplayTemperature, Temperature, TemperatureUnit};
 
 /// The type of component.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ComponentType {
     /// A CPU component.
     Cpu,
@@ -34,6 +46,281 @@ pub enum ComponentType {
     Sensor,
     /// A system component.
     System,
+    /// A storage component, such as an NVMe drive or hard disk.
+    Storage,
+    /// A memory component, such as a DIMM.
+    Memory,
+    /// A network component, such as a Wi-Fi card or Ethernet controller.
+    Network,
+    /// A chipset component, such as a PCH.
+    Chipset,
+    /// A power supply unit component.
+    Psu,
+}
+
+/// Represents a type of data that a sensor can return.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SensorKind {
+    /// Measures thermal data.
+    Temperature,
+    /// Measures voltage.
+    Voltage,
+    /// Measures current.
+    Current,
+    /// Measures power.
+    Power,
+    /// Measures fan speed.
+    Fan,
+    /// Measures energy consumption.
+    Energy,
+}
+
+impl SensorKind {
+    /// The unit values of this kind are measured in.
+    #[must_use]
+    pub const fn unit(self) -> &'static str {
+        match self {
+            Self::Temperature => "°C",
+            Self::Voltage => "V",
+            Self::Current => "A",
+            Self::Power => "W",
+            Self::Fan => "RPM",
+            Self::Energy => "J",
+        }
+    }
+}
+
+/// A stable identifier of a component or reading, built from what the reading is taken from
+/// rather than where it was enumerated, so it stays the same across rescans and reboots.
+///
+/// On Linux this is the sysfs interface, driver and device path of a component, e.g.
+/// `hwmon:k10temp@pci0000:00/0000:00:18.3`, followed by the channel for readings, e.g.
+/// `hwmon:k10temp@pci0000:00/0000:00:18.3#temp3`. On macOS it is the SMC key, e.g. `smc:TC0P`.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorId(String);
+
+impl SensorId {
+    /// Creates an ID from its string representation.
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// The string representation of this ID.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The ID of the given channel of this component, e.g. `temp3`.
+    #[must_use]
+    pub fn channel(&self, channel: &str) -> Self {
+        Self(format!("{}#{}", self.0, channel))
+    }
+
+    /// The ID of the component this ID belongs to, i.e. this ID without its channel.
+    #[must_use]
+    pub fn component(&self) -> Self {
+        match self.0.rsplit_once('#') {
+            Some((component, _)) => Self(component.to_string()),
+            None => self.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for SensorId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Whether a reading is up to date.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReadingStatus {
+    /// The reading was refreshed successfully.
+    #[default]
+    Ok,
+    /// The last refresh of the reading failed with the given error, so it holds its last known
+    /// value.
+    Stale(String),
+    /// The device the reading comes from is asleep, e.g. a runtime-suspended discrete GPU, so it
+    /// holds its last known value rather than waking the device up.
+    Sleeping,
+    /// The reading has never been read successfully, failing with the given error, so it has no
+    /// value.
+    Unavailable(String),
+}
+
+impl ReadingStatus {
+    /// The error that caused the reading to be stale or unavailable, if any.
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            Self::Ok | Self::Sleeping => None,
+            Self::Stale(error) | Self::Unavailable(error) => Some(error),
+        }
+    }
+
+    /// Whether the reading holds a value, even if it is stale.
+    #[must_use]
+    pub const fn has_value(&self) -> bool {
+        !matches!(self, Self::Unavailable(_))
+    }
+}
+
+/// A single reading of a sensor that measures something other than temperature, such as a
+/// voltage rail or the power draw of a GPU.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorReading {
+    /// The stable ID of this reading.
+    pub id: SensorId,
+    /// The label/name of what this reading represents.
+    pub label: String,
+    /// The type of data this reading measures.
+    pub kind: SensorKind,
+    /// The current value, in the unit given by [`SensorKind::unit`].
+    pub value: f64,
+    /// The lowest value considered normal, if known.
+    pub min: Option<f64>,
+    /// The highest value considered normal, if known.
+    pub max: Option<f64>,
+    /// Whether the value is up to date. Unavailable readings have a value of zero.
+    pub status: ReadingStatus,
+}
+
+/// Whether a battery is charging or discharging.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BatteryStatus {
+    /// The battery is being charged.
+    Charging,
+    /// The battery is powering the system.
+    Discharging,
+    /// The battery is fully charged.
+    Full,
+    /// The battery is plugged in but not being charged, e.g. because of a charge limit.
+    NotCharging,
+    /// The status could not be determined.
+    Unknown,
+}
+
+impl std::fmt::Display for BatteryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Charging => "Charging",
+            Self::Discharging => "Discharging",
+            Self::Full => "Full",
+            Self::NotCharging => "Not charging",
+            Self::Unknown => "Unknown",
+        })
+    }
+}
+
+/// The state of a battery as of its last refresh. Values the battery does not report are `None`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatteryState {
+    /// Whether the battery is charging or discharging.
+    pub status: BatteryStatus,
+    /// The energy currently stored in the battery, in watt-hours.
+    pub energy: Option<f64>,
+    /// The energy stored in the battery when it is full, in watt-hours.
+    pub energy_full: Option<f64>,
+    /// The rate the battery is being charged or discharged at, in watts.
+    pub power: Option<f64>,
+    /// The estimated time until the battery is empty, while discharging.
+    pub time_to_empty: Option<std::time::Duration>,
+    /// The estimated time until the battery is full, while charging.
+    pub time_to_full: Option<std::time::Duration>,
+}
+
+/// Where on a CPU a temperature reading is taken, and which logical CPUs it covers.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuLocation {
+    /// The physical package (socket) the reading belongs to.
+    pub package: u32,
+    /// The die within the package, i.e. the CCD on AMD processors, if the reading is specific to
+    /// one.
+    pub die: Option<u32>,
+    /// The core within the package, if the reading is specific to one.
+    pub core: Option<u32>,
+    /// The IDs of the logical CPUs covered by the reading.
+    pub cpus: Vec<u32>,
+}
+
+bitflags::bitflags! {
+    /// Alarms and faults raised by the hardware for a temperature reading.
+    #[derive(Default)]
+    pub struct TemperatureAlarms: u8 {
+        /// A limit was crossed, without the chip saying which one.
+        const ALARM = 1 << 0;
+        /// The temperature fell below the minimum.
+        const MIN = 1 << 1;
+        /// The temperature exceeded the maximum, i.e. it is high.
+        const MAX = 1 << 2;
+        /// The temperature fell below the low critical limit.
+        const LOW_CRITICAL = 1 << 3;
+        /// The temperature exceeded the critical limit.
+        const CRITICAL = 1 << 4;
+        /// The temperature exceeded the emergency limit.
+        const EMERGENCY = 1 << 5;
+        /// The sensor is faulty, e.g. a disconnected thermal diode, so its reading is bogus.
+        const FAULT = 1 << 6;
+    }
+}
+
+impl TemperatureAlarms {
+    /// Whether the sensor is faulty rather than reporting a crossed limit.
+    #[must_use]
+    pub const fn is_fault(self) -> bool {
+        self.contains(Self::FAULT)
+    }
+
+    /// The name of each flag, as it is serialized.
+    const NAMES: [(Self, &'static str); 7] = [
+        (Self::ALARM, "alarm"),
+        (Self::MIN, "min"),
+        (Self::MAX, "max"),
+        (Self::LOW_CRITICAL, "low_critical"),
+        (Self::CRITICAL, "critical"),
+        (Self::EMERGENCY, "emergency"),
+        (Self::FAULT, "fault"),
+    ];
+}
+
+/// Serialized as the list of names of the raised flags, e.g. `["max", "critical"]`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for TemperatureAlarms {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            Self::NAMES
+                .iter()
+                .filter(|(flag, _)| self.contains(*flag))
+                .map(|(_, name)| name),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TemperatureAlarms {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .try_fold(Self::empty(), |alarms, name| {
+                Self::NAMES
+                    .iter()
+                    .find(|(_, n)| n == name)
+                    .map(|(flag, _)| alarms | *flag)
+                    .ok_or_else(|| {
+                        serde::de::Error::custom(format!("unknown temperature alarm {:?}", name))
+                    })
+            })
+    }
 }
 
 /// Common interface that represents a single temperature reading.
@@ -41,17 +328,78 @@ pub trait TemperatureReading {
     /// The label/name of what this temperature represents.
     fn label(&self) -> String;
 
-    /// The current reading in degrees Celsius.
-    fn temperature(&self) -> f64;
+    /// The stable ID of this reading. By default this is the label, which is only unique if the
+    /// labels of a component's readings are.
+    fn id(&self) -> SensorId {
+        SensorId::new(self.label())
+    }
+
+    /// The current reading.
+    fn temperature(&self) -> Temperature;
 
-    /// The maximum recorded temperature in degrees Celsius.
-    fn max(&self) -> f64;
+    /// The maximum recorded temperature.
+    fn max(&self) -> Temperature;
 
-    /// The temperature that will be considered "high", in degrees Celsius.
-    fn high(&self) -> f64;
+    /// The temperature that will be considered "high".
+    fn high(&self) -> Temperature;
 
-    /// The temperature that will be considered "critical", in degrees Celsius.
-    fn critical(&self) -> f64;
+    /// The temperature that will be considered "critical".
+    fn critical(&self) -> Temperature;
+
+    /// The minimum temperature limit, if the hardware has one.
+    fn min(&self) -> Option<Temperature> {
+        None
+    }
+
+    /// The minimum recorded temperature, if the hardware records one.
+    fn lowest(&self) -> Option<Temperature> {
+        None
+    }
+
+    /// The low critical temperature limit, if the hardware has one.
+    fn low_critical(&self) -> Option<Temperature> {
+        None
+    }
+
+    /// The emergency temperature limit, above even the critical one, if the hardware has one.
+    fn emergency(&self) -> Option<Temperature> {
+        None
+    }
+
+    /// The temperature the reading has to fall back to for a high temperature alarm to clear, if
+    /// the hardware has one.
+    fn high_hysteresis(&self) -> Option<Temperature> {
+        None
+    }
+
+    /// The temperature the reading has to fall back to for a critical temperature alarm to
+    /// clear, if the hardware has one.
+    fn critical_hysteresis(&self) -> Option<Temperature> {
+        None
+    }
+
+    /// The alarms and faults currently raised by the hardware. By default there are none.
+    fn alarms(&self) -> TemperatureAlarms {
+        TemperatureAlarms::empty()
+    }
+
+    /// Whether this reading is up to date. Unavailable readings should not be taken into account,
+    /// as their temperature is meaningless. By default readings are always up to date.
+    fn status(&self) -> ReadingStatus {
+        ReadingStatus::Ok
+    }
+
+    /// The utilisation of what this temperature is measured on, e.g. a CPU core, from 0.0 to
+    /// 100.0. By default this is unknown.
+    fn usage(&self) -> Option<f32> {
+        None
+    }
+
+    /// Where on the CPU this temperature is measured, if it is a CPU temperature that could be
+    /// mapped to the CPU topology. By default this is unknown.
+    fn cpu_location(&self) -> Option<CpuLocation> {
+        None
+    }
 }
 
 /// Common interface that represents a temperature-measurable system component.
@@ -61,25 +409,52 @@ pub trait Component {
     /// The label of the component.
     fn label(&self) -> String;
 
-    /// The current temperature readings of the component, in celsius.
+    /// The stable ID of the component. By default this is the label.
+    fn id(&self) -> SensorId {
+        SensorId::new(self.label())
+    }
+
+    /// The current temperature readings of the component.
     fn temperatures(&self) -> Vec<Self::TemperatureReading>;
 
-    /// The CPU, GPU, or battery percentage of the component, from 0.0 to 100.0.
+    /// The current non-thermal sensor readings of the component, such as voltages or power draw.
+    /// By default there are none.
+    fn sensors(&self) -> Vec<SensorReading> {
+        Vec::new()
+    }
+
+    /// The charge state of the component, if it is a battery. By default this is `None`.
+    fn battery(&self) -> Option<BatteryState> {
+        None
+    }
+
+    /// The CPU, GPU, battery, or fan speed percentage of the component, from 0.0 to 100.0.
     fn percentage(&self) -> Option<f32> {
         None
     }
 
+    /// The current fan speed of the component in RPM, if it is a fan.
+    fn rpm(&self) -> Option<f64> {
+        None
+    }
+
     /// The type of the component.
     fn component_type(&self) -> ComponentType;
 
     /// Updates the component's data, if needed. By default this is a no-op.
-    fn refresh(&mut self) -> Result<(), String> {
+    fn refresh(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Resets the recorded minimum and maximum temperatures, if the hardware records them. By
+    /// default this is a no-op.
+    fn reset_history(&mut self) -> Result<(), Error> {
         Ok(())
     }
 }
 
 /// Common interface for interacting with system components.
-pub trait Interface: Default {
+pub trait Interface {
     /// The type of the component this interface uses.
     type Component: Component;
 
@@ -112,27 +487,164 @@ pub trait Interface: Default {
             .collect()
     }
 
+    /// Returns a Vec of all [`Component`]s that provide non-thermal sensor readings. By default
+    /// these are the thermal components that have any [`Component::sensors`].
+    fn sensor_components(&self) -> Vec<&Self::Component> {
+        self.thermal_components()
+            .into_iter()
+            .filter(|c| !c.sensors().is_empty())
+            .collect()
+    }
+
+    /// Returns a Vec of all fan [`Component`]s. By default there are none.
+    fn fan_components(&self) -> Vec<&Self::Component> {
+        Vec::new()
+    }
+
+    /// Returns a Vec of all fan [`Component`]s. This one should return mutable references.
+    fn fan_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        Vec::new()
+    }
+
+    /// Returns a Vec of all battery [`Component`]s. By default there are none.
+    fn battery_components(&self) -> Vec<&Self::Component> {
+        Vec::new()
+    }
+
+    /// Returns a Vec of all battery [`Component`]s. This one should return mutable references.
+    fn battery_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        Vec::new()
+    }
+
+    /// Returns the component with the given ID, or the component the reading with the given ID
+    /// belongs to. By default this searches every thermal, fan, battery and sensor component, but
+    /// it can be overridden with a lookup.
+    fn component_by_id(&self, id: &SensorId) -> Option<&Self::Component> {
+        let components = self
+            .thermal_components()
+            .into_iter()
+            .chain(self.fan_components())
+            .chain(self.battery_components())
+            .chain(self.sensor_components())
+            .collect::<Vec<_>>();
+        let component = id.component();
+
+        components
+            .iter()
+            .find(|c| c.id() == *id)
+            .or_else(|| components.iter().find(|c| c.id() == component))
+            .copied()
+    }
+
+    /// Returns the current temperature reading with the given ID, if any.
+    fn temperature_by_id(
+        &self,
+        id: &SensorId,
+    ) -> Option<<Self::Component as Component>::TemperatureReading> {
+        self.component_by_id(id)?
+            .temperatures()
+            .into_iter()
+            .find(|reading| reading.id() == *id)
+    }
+
     /// The OS name of the interface.
     fn os_name(&self) -> String;
 
     /// The name of the CPU or core processor.
     fn cpu_name(&self) -> String;
 
+    /// The name of the GPU, if known.
+    fn gpu_name(&self) -> Option<String> {
+        None
+    }
+
     /// The model of the device.
     fn device_model_name(&self) -> String;
 
+    /// The version of the OS kernel, if known.
+    fn kernel_version(&self) -> Option<String> {
+        None
+    }
+
+    /// The manufacturer of the motherboard, if known.
+    fn board_vendor(&self) -> Option<String> {
+        None
+    }
+
+    /// The model of the motherboard, if known.
+    fn board_name(&self) -> Option<String> {
+        None
+    }
+
+    /// The version of the BIOS or other system firmware, if known.
+    fn bios_version(&self) -> Option<String> {
+        None
+    }
+
+    /// The number of physical CPU cores, if known.
+    fn core_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// The number of logical CPUs, i.e. hardware threads, if known.
+    fn thread_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// Captures the current state of every component, with all of its readings and thresholds,
+    /// along with the system information, as plain data. This does not refresh the interface
+    /// first.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot::capture(self)
+    }
+
     /// Refreshes the interface for the next iteration. By default this refreshes every component
-    /// received in [`Interface::thermal_components_mut`].
-    fn refresh(&mut self) -> Result<(), String> {
+    /// received in [`Interface::thermal_components_mut`], [`Interface::fan_components_mut`] and
+    /// [`Interface::battery_components_mut`].
+    fn refresh(&mut self) -> Result<(), Error> {
         for component in self.thermal_components_mut() {
             component.refresh()?;
         }
 
+        for component in self.fan_components_mut() {
+            component.refresh()?;
+        }
+
+        for component in self.battery_components_mut() {
+            component.refresh()?;
+        }
+
         Ok(())
     }
+
+    /// Resets the recorded minimum and maximum temperatures of every component received in
+    /// [`Interface::thermal_components_mut`].
+    fn reset_history(&mut self) -> Result<(), Error> {
+        for component in self.thermal_components_mut() {
+            component.reset_history()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Hands every fan that has been manually overridden back to its original control mode. This
+/// should be called before exiting, and is safe to call from a panic hook or signal handler
+/// thread. On platforms without fan control this is a no-op.
+pub fn restore_fans() -> Result<(), Error> {
+    #[cfg(target_os = "linux")]
+    linux::restore_fans()?;
+
+    Ok(())
 }
 
+/// The [`Interface`] of the current platform. Create one with `Provider::new()`, which returns an
+/// [`Error`] if sensors can't be accessed, e.g. because the SMC can't be opened, whereas
+/// `Provider::default()` panics.
 #[cfg(target_os = "macos")]
 pub use apple::AppleComponents as Provider;
+/// The [`Interface`] of the current platform. Create one with `Provider::new()`, which returns an
+/// [`Error`] if sensors can't be accessed, e.g. because sysfs can't be read, whereas
+/// `Provider::default()` panics.
 #[cfg(target_os = "linux")]
 pub use linux::LinuxComponents as Provider;
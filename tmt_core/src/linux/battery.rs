@@ -0,0 +1,219 @@
+//! Batteries exposed through /sys/class/power_supply.
+//!
+//! Depending on the driver, batteries report the energy they hold either directly (`energy_*`,
+//! in µWh, with `power_now` in µW) or as charge (`charge_*`, in µAh, with `current_now` in µA),
+//! in which case it is converted to energy using the battery's voltage.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::{component_id, LinuxError, SystemRoot, TemperatureReading};
+use crate::{BatteryState, BatteryStatus, SensorId, Temperature};
+
+/// A battery read from /sys/class/power_supply/BAT*.
+pub struct PowerSupplyBattery {
+    id: SensorId,
+    pub(super) path: PathBuf,
+    name: String,
+    model: Option<String>,
+    capacity: Option<f32>,
+    temperature: Option<i32>,
+    high: Option<i32>,
+    crit: Option<i32>,
+    state: BatteryState,
+}
+
+impl PowerSupplyBattery {
+    fn new(id: SensorId, path: PathBuf, name: String) -> Self {
+        let mut battery = Self {
+            id,
+            path,
+            name,
+            model: None,
+            capacity: None,
+            temperature: None,
+            high: None,
+            crit: None,
+            state: BatteryState {
+                status: BatteryStatus::Unknown,
+                energy: None,
+                energy_full: None,
+                power: None,
+                time_to_empty: None,
+                time_to_full: None,
+            },
+        };
+
+        battery.model = battery.read_string("model_name");
+        battery
+    }
+
+    fn read_string(&self, attribute: &str) -> Option<String> {
+        std::fs::read_to_string(self.path.join(attribute))
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn read(&self, attribute: &str) -> Option<i64> {
+        self.read_string(attribute)?.parse().ok()
+    }
+
+    /// The stable ID of the battery.
+    #[must_use]
+    pub const fn id(&self) -> &SensorId {
+        &self.id
+    }
+
+    /// The name of the battery, e.g. `BAT0`.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The model of the battery, if reported.
+    #[must_use]
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// The remaining capacity of the battery, from 0.0 to 100.0.
+    #[must_use]
+    pub const fn capacity(&self) -> Option<f32> {
+        self.capacity
+    }
+
+    /// The state of the battery as of its last refresh.
+    #[must_use]
+    pub const fn state(&self) -> &BatteryState {
+        &self.state
+    }
+
+    /// The temperature of the battery, if the driver exposes one.
+    #[must_use]
+    pub fn temperature(&self) -> Option<TemperatureReading> {
+        // power_supply temperatures are in tenths of a degree, hwmon's are in thousandths.
+        let tenths = |tenths: i32| Temperature::from_millidegrees(tenths.saturating_mul(100));
+
+        Some(TemperatureReading {
+            id: self.id.channel("temp"),
+            name: self.name.clone(),
+            temperature: tenths(self.temperature?),
+            high: tenths(self.high.unwrap_or(450)),
+            crit: tenths(self.crit.unwrap_or(600)),
+            ..TemperatureReading::default()
+        })
+    }
+
+    pub(super) fn refresh(&mut self) -> Result<(), LinuxError> {
+        // Charge is converted to energy with the design voltage if known, as the current voltage
+        // drops as the battery discharges.
+        let voltage = self.read("voltage_now");
+        let design_voltage = self.read("voltage_min_design").or(voltage);
+        let from_micro = |micro: i64| micro as f64 / 1e6;
+        let from_charge = |charge: i64, voltage: i64| charge as f64 * voltage as f64 / 1e12;
+
+        let energy = self
+            .read("energy_now")
+            .map(from_micro)
+            .or_else(|| Some(from_charge(self.read("charge_now")?, design_voltage?)));
+        let energy_full = self
+            .read("energy_full")
+            .map(from_micro)
+            .or_else(|| Some(from_charge(self.read("charge_full")?, design_voltage?)));
+        // Some drivers report a negative rate while discharging.
+        let power = self
+            .read("power_now")
+            .map(|power| from_micro(power.abs()))
+            .or_else(|| Some(from_charge(self.read("current_now")?.abs(), voltage?)));
+
+        let status = match self.read_string("status").as_deref() {
+            Some("Charging") => BatteryStatus::Charging,
+            Some("Discharging") => BatteryStatus::Discharging,
+            Some("Full") => BatteryStatus::Full,
+            Some("Not charging") => BatteryStatus::NotCharging,
+            _ => BatteryStatus::Unknown,
+        };
+
+        // Batteries report more energy than when full after recalibrating, so there may be no
+        // energy left to charge.
+        let hours = |energy: f64| {
+            power
+                .filter(|power| *power > 0.0 && energy > 0.0)
+                .and_then(|power| Duration::try_from_secs_f64(energy / power * 3600.0).ok())
+        };
+        let reported = |attribute| self.read(attribute).and_then(|s| u64::try_from(s).ok());
+
+        let time_to_empty = match status {
+            BatteryStatus::Discharging => reported("time_to_empty_now")
+                .map(Duration::from_secs)
+                .or_else(|| hours(energy?)),
+            _ => None,
+        };
+        let time_to_full = match status {
+            BatteryStatus::Charging => reported("time_to_full_now")
+                .map(Duration::from_secs)
+                .or_else(|| hours(energy_full? - energy?)),
+            _ => None,
+        };
+
+        self.capacity = self
+            .read("capacity")
+            .map(|capacity| capacity as f32)
+            .or_else(|| {
+                let energy_full = energy_full.filter(|energy_full| *energy_full > 0.0)?;
+                Some((energy? / energy_full * 100.0).clamp(0.0, 100.0) as f32)
+            });
+        self.temperature = self.read("temp").and_then(|t| i32::try_from(t).ok());
+        self.high = self
+            .read("temp_alert_max")
+            .and_then(|t| i32::try_from(t).ok());
+        self.crit = self.read("temp_max").and_then(|t| i32::try_from(t).ok());
+        self.state = BatteryState {
+            status,
+            energy,
+            energy_full,
+            power,
+            time_to_empty,
+            time_to_full,
+        };
+
+        Ok(())
+    }
+}
+
+/// Returns every system battery in /sys/class/power_supply. Mains adapters and the batteries of
+/// peripherals, such as wireless mice, are left out.
+pub(super) fn get_batteries(root: &SystemRoot) -> Result<Vec<PowerSupplyBattery>, LinuxError> {
+    let mut batteries = Vec::new();
+    let path = root.resolve("/sys/class/power_supply");
+
+    if !path.exists() {
+        return Ok(batteries);
+    }
+
+    for entry in path.read_dir()? {
+        let entry = entry?;
+        let path = entry.path();
+        let read = |attribute: &str| {
+            std::fs::read_to_string(path.join(attribute))
+                .ok()
+                .map(|value| value.trim().to_string())
+        };
+
+        let is_battery = read("type").as_deref() == Some("Battery");
+        let is_peripheral = read("scope").as_deref() == Some("Device");
+        let is_present = read("present").as_deref() != Some("0");
+
+        if !is_battery || is_peripheral || !is_present {
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let id = component_id(root, "power_supply", &name, &path);
+        batteries.push(PowerSupplyBattery::new(id, path, name));
+    }
+
+    batteries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(batteries)
+}
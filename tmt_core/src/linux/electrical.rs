@@ -0,0 +1,131 @@
+//! Voltage, current, power and energy channels exposed through hwmon `in*`, `curr*`, `power*` and
+//! `energy*` attributes.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{ChipConfig, LinuxError, LinuxError::InvalidData};
+use crate::{ReadingStatus, SensorId, SensorKind, SensorReading};
+
+/// The hwmon attribute prefixes of every electrical channel type, along with the kind of data they
+/// measure and the factor that converts hwmon's units (mV, mA, µW and µJ) to base units.
+const CHANNELS: [(&str, SensorKind, f64); 4] = [
+    ("in", SensorKind::Voltage, 1e-3),
+    ("curr", SensorKind::Current, 1e-3),
+    ("power", SensorKind::Power, 1e-6),
+    ("energy", SensorKind::Energy, 1e-6),
+];
+
+/// Splits an attribute name such as `power1_average` into the position of its channel type in
+/// [`CHANNELS`], its channel index and the attribute itself, e.g. `(2, 1, "average")`.
+fn parse_attribute(name: &str) -> Option<(usize, u32, &str)> {
+    CHANNELS.iter().enumerate().find_map(|(i, (prefix, ..))| {
+        let (index, attribute) = name.strip_prefix(prefix)?.split_once('_')?;
+        Some((i, index.parse().ok()?, attribute))
+    })
+}
+
+/// Whether the given attribute holds the current value of a channel. Some drivers, such as amdgpu,
+/// only provide an averaged power reading.
+fn is_input(channel: usize, attribute: &str) -> bool {
+    attribute == "input" || CHANNELS[channel].1 == SensorKind::Power && attribute == "average"
+}
+
+/// Whether the given hwmon directory has any electrical channels.
+pub(super) fn has_channels(path: &Path) -> bool {
+    path.read_dir().is_ok_and(|entries| {
+        entries.filter_map(Result::ok).any(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(parse_attribute)
+                .is_some_and(|(channel, _, attribute)| is_input(channel, attribute))
+        })
+    })
+}
+
+/// Reads every electrical channel of the given hwmon directory, ordered by type and index. Their
+/// IDs are the channels of the chip's ID, e.g. `#in0`. The chip's lm-sensors configuration labels,
+/// ignores and converts channels. Channels that fail to read keep their value from `previous` and
+/// are marked as stale, or are marked as unavailable if they have never been read. Only failing to
+/// list the directory is an error.
+pub(super) fn read_channels(
+    path: &Path,
+    id: &SensorId,
+    config: &ChipConfig,
+    previous: &[SensorReading],
+) -> Result<Vec<SensorReading>, LinuxError> {
+    let mut inputs = BTreeMap::new();
+
+    for entry in path.read_dir()?.filter_map(Result::ok) {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+
+        if let Some((channel, index, attribute)) = parse_attribute(name) {
+            // Prefer powerN_input over powerN_average when a driver provides both.
+            if is_input(channel, attribute)
+                && (attribute == "input" || !inputs.contains_key(&(channel, index)))
+            {
+                inputs.insert((channel, index), name.to_string());
+            }
+        }
+    }
+
+    let mut readings = Vec::with_capacity(inputs.len());
+
+    for ((channel, index), input) in inputs {
+        let (prefix, kind, scale) = CHANNELS[channel];
+        let name = format!("{}{}", prefix, index);
+        if config.is_ignored(&name) {
+            continue;
+        }
+
+        let id = id.channel(&name);
+        let compute = |value: f64| config.compute_in(path, &name, value);
+        let read = |attribute: &str| {
+            std::fs::read_to_string(path.join(format!("{}_{}", name, attribute)))
+                .ok()
+                .and_then(|s| s.trim().parse::<i64>().ok())
+                .map(|value| compute(value as f64 * scale))
+        };
+
+        let label = match config.label(&name) {
+            Some(label) => label.to_string(),
+            None => std::fs::read_to_string(path.join(format!("{}_label", name)))
+                .map_or_else(|_| name.clone(), |label| label.trim().to_string()),
+        };
+
+        let value = std::fs::read_to_string(path.join(&input))
+            .map_err(LinuxError::from)
+            .and_then(|value| {
+                value.trim().parse::<i64>().map_err(|_| {
+                    InvalidData(format!("read invalid {} value {}", prefix, value.trim()))
+                })
+            });
+
+        let (value, status) = match value {
+            Ok(value) => (compute(value as f64 * scale), ReadingStatus::Ok),
+            Err(err) => match previous.iter().find(|reading| reading.id == id) {
+                Some(reading) if reading.status.has_value() => {
+                    (reading.value, ReadingStatus::Stale(err.to_string()))
+                }
+                _ => (0.0, ReadingStatus::Unavailable(err.to_string())),
+            },
+        };
+
+        readings.push(SensorReading {
+            id,
+            label,
+            kind,
+            value,
+            status,
+            min: read("min"),
+            // Power channels usually expose their limit as a cap rather than a maximum.
+            max: read("max").or_else(|| read("cap").filter(|_| kind == SensorKind::Power)),
+        });
+    }
+
+    Ok(readings)
+}
@@ -0,0 +1,486 @@
+//! Fans exposed through hwmon `fan*` and `pwm*` attributes.
+//!
+//! Fans can be controlled by switching their `pwmN_enable` attribute to manual mode and writing to
+//! `pwmN`. Whenever this happens the original `pwmN_enable` and `pwmN` values are recorded, and are
+//! put back when the fan is restored with [`HwmonFan::restore`], when it is dropped, or when
+//! [`restore_fans`] is called, e.g. from a panic hook or signal handler.
+
+use std::collections::{hash_map::Entry, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use super::{hwmon_id, is_asleep, ChipConfig, LinuxError, LinuxError::InvalidData, SystemRoot};
+use crate::{ReadingStatus, SensorId};
+
+/// The lowest duty cycle, in percent, that a fan may be set to manually. Anything lower risks
+/// stalling the fan.
+pub const MIN_SAFE_DUTY_CYCLE: f32 = 20.0;
+
+/// The original state of a fan that has been taken over.
+#[derive(Copy, Clone, Debug)]
+struct OriginalState {
+    enable: u8,
+    pwm: Option<u8>,
+}
+
+lazy_static::lazy_static! {
+    /// The original state of every fan that has been taken over, keyed by its `pwmN` path.
+    static ref OVERRIDDEN: Mutex<HashMap<PathBuf, OriginalState>> = Mutex::new(HashMap::new());
+}
+
+fn overridden() -> std::sync::MutexGuard<'static, HashMap<PathBuf, OriginalState>> {
+    // A panic while holding the lock must not prevent fans from being restored afterwards.
+    OVERRIDDEN
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn write_restored(pwm_path: &Path, original: OriginalState) -> Result<(), LinuxError> {
+    let mut enable_path = pwm_path.as_os_str().to_owned();
+    enable_path.push("_enable");
+
+    // Writing to pwmN is only accepted in manual mode, so this has to happen before the control
+    // mode is restored.
+    if let (1, Some(pwm)) = (original.enable, original.pwm) {
+        std::fs::write(pwm_path, pwm.to_string())?;
+    }
+
+    std::fs::write(enable_path, original.enable.to_string())?;
+    Ok(())
+}
+
+/// Restores every fan that has been taken over to its original control mode and speed. This is
+/// safe to call at any time, including from a panic hook, and is a no-op if no fans have been
+/// taken over. Every fan is attempted even if restoring one of them fails, in which case the first
+/// error is returned.
+pub fn restore_fans() -> Result<(), LinuxError> {
+    let mut result = Ok(());
+
+    for (path, original) in overridden().drain() {
+        let restored = write_restored(&path, original);
+
+        if result.is_ok() {
+            result = restored;
+        }
+    }
+
+    result
+}
+
+/// How a hwmon fan's PWM output is controlled, read from `pwmN_enable`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FanControlMode {
+    /// No fan speed control, i.e. the fan runs at full speed (`0`).
+    FullSpeed,
+    /// Manual control through `pwmN` (`1`).
+    Manual,
+    /// Automatic control by the chip or its driver. The exact meaning is driver-specific, for
+    /// example nct6775 uses `5` for SmartFan IV.
+    Automatic(u8),
+}
+
+impl FanControlMode {
+    /// Returns the control mode from the given `pwmN_enable` value.
+    #[must_use]
+    pub const fn from_enable(value: u8) -> Self {
+        match value {
+            0 => Self::FullSpeed,
+            1 => Self::Manual,
+            value => Self::Automatic(value),
+        }
+    }
+
+    /// The `pwmN_enable` value of this control mode.
+    #[must_use]
+    pub const fn enable_value(self) -> u8 {
+        match self {
+            Self::FullSpeed => 0,
+            Self::Manual => 1,
+            Self::Automatic(value) => value,
+        }
+    }
+}
+
+/// A single fan channel of a hwmon chip, i.e. `fanN_input` and its accompanying `pwmN` output.
+pub struct HwmonFan {
+    id: SensorId,
+    pub(super) path: PathBuf,
+    pub(super) device_path: PathBuf,
+    chip: Option<String>,
+    index: u32,
+    label: String,
+    rpm: Option<u32>,
+    min_rpm: Option<u32>,
+    max_rpm: Option<u32>,
+    pwm: Option<u8>,
+    pwm_max: u8,
+    mode: Option<FanControlMode>,
+    sleeping: bool,
+    /// Why the speed couldn't be read at the last refresh, if it couldn't.
+    error: Option<String>,
+    /// The lm-sensors configuration of the fan's chip.
+    pub(super) config: ChipConfig,
+    /// Whether this instance took the fan over, and so is responsible for restoring it on drop.
+    /// Rescanning creates other instances of the same fan, which must not restore it.
+    took_over: bool,
+}
+
+impl HwmonFan {
+    #[must_use]
+    fn new(
+        chip_id: &SensorId,
+        path: PathBuf,
+        device_path: PathBuf,
+        chip: Option<String>,
+        index: u32,
+    ) -> Self {
+        let label = std::fs::read_to_string(path.join(format!("fan{}_label", index)))
+            .map(|label| label.trim().to_string())
+            .unwrap_or_else(|_| match &chip {
+                Some(chip) => format!("{} Fan {}", chip, index),
+                None => format!("Fan {}", index),
+            });
+
+        Self {
+            id: chip_id.channel(&format!("fan{}", index)),
+            path,
+            device_path,
+            chip,
+            index,
+            label,
+            rpm: None,
+            min_rpm: None,
+            max_rpm: None,
+            pwm: None,
+            pwm_max: u8::MAX,
+            mode: None,
+            sleeping: false,
+            error: None,
+            config: ChipConfig::default(),
+            took_over: false,
+        }
+    }
+
+    fn read<T: FromStr>(&self, attribute: &str) -> Option<T> {
+        std::fs::read_to_string(self.path.join(attribute))
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+    }
+
+    /// The name of the hwmon chip this fan belongs to.
+    #[must_use]
+    pub fn chip(&self) -> Option<&str> {
+        self.chip.as_deref()
+    }
+
+    /// The channel index of this fan, i.e. `N` in `fanN_input`.
+    #[must_use]
+    pub const fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The stable ID of the fan, i.e. the `fanN` channel of its chip's ID.
+    #[must_use]
+    pub const fn id(&self) -> &SensorId {
+        &self.id
+    }
+
+    /// The label of this fan, from the lm-sensors configuration or `fanN_label` if either
+    /// provides one.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        self.config
+            .label(&format!("fan{}", self.index))
+            .unwrap_or(&self.label)
+    }
+
+    /// Whether the lm-sensors configuration ignores this fan.
+    pub(super) fn is_ignored(&self) -> bool {
+        self.config.is_ignored(&format!("fan{}", self.index))
+    }
+
+    /// The current fan speed in RPM.
+    #[must_use]
+    pub const fn rpm(&self) -> Option<u32> {
+        self.rpm
+    }
+
+    /// The minimum fan speed in RPM, if the chip provides one.
+    #[must_use]
+    pub const fn min_rpm(&self) -> Option<u32> {
+        self.min_rpm
+    }
+
+    /// The maximum fan speed in RPM, if the chip provides one.
+    #[must_use]
+    pub const fn max_rpm(&self) -> Option<u32> {
+        self.max_rpm
+    }
+
+    /// The raw PWM value of this fan's output, usually from 0 to 255.
+    #[must_use]
+    pub const fn pwm(&self) -> Option<u8> {
+        self.pwm
+    }
+
+    /// The PWM duty cycle of this fan's output, from 0.0 to 100.0.
+    #[must_use]
+    pub fn duty_cycle(&self) -> Option<f32> {
+        self.pwm
+            .map(|pwm| f32::from(pwm) / f32::from(self.pwm_max.max(1)) * 100.0)
+    }
+
+    /// How this fan's PWM output is currently controlled.
+    #[must_use]
+    pub const fn mode(&self) -> Option<FanControlMode> {
+        self.mode
+    }
+
+    /// Whether the device the fan belongs to is asleep, e.g. a runtime-suspended GPU, in which
+    /// case it isn't read and keeps its last known speed.
+    #[must_use]
+    pub const fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Whether [`HwmonFan::rpm`] is up to date. A fan whose speed can't be read, e.g. because of
+    /// a bus error, keeps its last known speed.
+    #[must_use]
+    pub fn status(&self) -> ReadingStatus {
+        match (&self.error, self.rpm) {
+            _ if self.sleeping => ReadingStatus::Sleeping,
+            (None, _) => ReadingStatus::Ok,
+            (Some(err), Some(_)) => ReadingStatus::Stale(err.clone()),
+            (Some(err), None) => ReadingStatus::Unavailable(err.clone()),
+        }
+    }
+
+    /// The fan speed as a percentage between its minimum and maximum speed, from 0.0 to 100.0.
+    /// Falls back to the PWM duty cycle when the chip does not provide a maximum speed.
+    #[must_use]
+    pub fn percentage(&self) -> Option<f32> {
+        match (self.rpm, self.min_rpm.unwrap_or(0), self.max_rpm) {
+            (Some(rpm), min, Some(max)) if max > min => {
+                let rpm = rpm.saturating_sub(min) as f32;
+                Some((rpm / (max - min) as f32 * 100.0).min(100.0))
+            }
+            _ => self.duty_cycle(),
+        }
+    }
+
+    fn pwm_path(&self) -> PathBuf {
+        self.path.join(format!("pwm{}", self.index))
+    }
+
+    /// Whether this fan's speed can be controlled, i.e. it has `pwmN` and `pwmN_enable`
+    /// attributes.
+    #[must_use]
+    pub fn is_controllable(&self) -> bool {
+        self.pwm_path().exists() && self.path.join(format!("pwm{}_enable", self.index)).exists()
+    }
+
+    /// Whether this fan has been taken over and not yet been restored.
+    #[must_use]
+    pub fn is_overridden(&self) -> bool {
+        overridden().contains_key(&self.pwm_path())
+    }
+
+    /// Sets how this fan's PWM output is controlled. The first time the mode is changed, the
+    /// original mode and PWM value are recorded so that they can be restored later.
+    pub fn set_mode(&mut self, mode: FanControlMode) -> Result<(), LinuxError> {
+        if !self.is_controllable() {
+            return Err(InvalidData(format!(
+                "{} cannot be controlled",
+                self.label()
+            )));
+        }
+
+        let pwm_path = self.pwm_path();
+        let enable_path = self.path.join(format!("pwm{}_enable", self.index));
+
+        if let Entry::Vacant(entry) = overridden().entry(pwm_path) {
+            let enable = std::fs::read_to_string(&enable_path)?;
+            let enable = enable
+                .trim()
+                .parse()
+                .map_err(|_| InvalidData(format!("read invalid pwm enable value {}", enable)))?;
+
+            entry.insert(OriginalState {
+                enable,
+                pwm: self.read(&format!("pwm{}", self.index)),
+            });
+        }
+
+        self.took_over = true;
+        std::fs::write(enable_path, mode.enable_value().to_string())?;
+        self.mode = Some(mode);
+        Ok(())
+    }
+
+    /// Switches this fan to manual control and sets its raw PWM value. Values that would put the
+    /// fan below [`MIN_SAFE_DUTY_CYCLE`] or below the chip's `pwmN_min`, or above the chip's
+    /// maximum PWM value, are refused with [`LinuxError::UnsafeFanSpeed`].
+    pub fn set_pwm(&mut self, pwm: u8) -> Result<(), LinuxError> {
+        let pwm_max = self
+            .read(&format!("pwm{}_max", self.index))
+            .unwrap_or(u8::MAX);
+        let safe_min = (f32::from(pwm_max) * MIN_SAFE_DUTY_CYCLE / 100.0).ceil() as u8;
+        let pwm_min = self
+            .read(&format!("pwm{}_min", self.index))
+            .unwrap_or(0)
+            .max(safe_min);
+
+        if pwm < pwm_min || pwm > pwm_max {
+            return Err(LinuxError::UnsafeFanSpeed);
+        }
+
+        self.set_mode(FanControlMode::Manual)?;
+        std::fs::write(self.pwm_path(), pwm.to_string())?;
+        self.pwm = Some(pwm);
+        Ok(())
+    }
+
+    /// Switches this fan to manual control and sets its duty cycle, from
+    /// [`MIN_SAFE_DUTY_CYCLE`] to 100.0. See [`HwmonFan::set_pwm`].
+    pub fn set_duty_cycle(&mut self, duty_cycle: f32) -> Result<(), LinuxError> {
+        if !(MIN_SAFE_DUTY_CYCLE..=100.0).contains(&duty_cycle) {
+            return Err(LinuxError::UnsafeFanSpeed);
+        }
+
+        let pwm_max = self
+            .read(&format!("pwm{}_max", self.index))
+            .unwrap_or(u8::MAX);
+        self.set_pwm((f32::from(pwm_max) * duty_cycle / 100.0).round() as u8)
+    }
+
+    /// Hands this fan back to its original control mode and PWM value. This is a no-op if the
+    /// fan has not been taken over.
+    pub fn restore(&mut self) -> Result<(), LinuxError> {
+        let pwm_path = self.pwm_path();
+        let original = overridden().remove(&pwm_path);
+        self.took_over = false;
+
+        if let Some(original) = original {
+            write_restored(&pwm_path, original)?;
+            self.mode = Some(FanControlMode::from_enable(original.enable));
+            self.pwm = original.pwm.or(self.pwm);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn refresh(&mut self) -> Result<(), LinuxError> {
+        // Reading a sleeping device, e.g. a runtime-suspended GPU, wakes it up.
+        self.sleeping = is_asleep(&self.device_path);
+        if self.sleeping {
+            return Ok(());
+        }
+
+        let channel = format!("fan{}", self.index);
+        let compute = |rpm: u32| {
+            let rpm = self.config.compute_in(&self.path, &channel, f64::from(rpm));
+            rpm.max(0.0).round() as u32
+        };
+
+        let rpm = std::fs::read_to_string(self.path.join(format!("{}_input", channel)))
+            .map_err(LinuxError::from)
+            .and_then(|rpm| {
+                rpm.trim()
+                    .parse()
+                    .map_err(|_| InvalidData(format!("read invalid fan speed {}", rpm.trim())))
+            });
+
+        // A fan that fails to read keeps its last speed rather than failing the refresh.
+        match rpm {
+            Ok(rpm) => {
+                self.rpm = Some(compute(rpm));
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+
+        self.min_rpm = self.read(&format!("{}_min", channel)).map(compute);
+        self.max_rpm = self.read(&format!("{}_max", channel)).map(compute);
+        self.pwm = self.read(&format!("pwm{}", self.index));
+        self.pwm_max = self
+            .read(&format!("pwm{}_max", self.index))
+            .unwrap_or(u8::MAX);
+        self.mode = self
+            .read(&format!("pwm{}_enable", self.index))
+            .map(FanControlMode::from_enable);
+
+        Ok(())
+    }
+}
+
+impl Drop for HwmonFan {
+    fn drop(&mut self) {
+        if self.took_over {
+            let _ = self.restore();
+        }
+    }
+}
+
+/// Returns the indices of every `fanN_input` attribute in the given directory, in order.
+fn fan_indices(path: &Path) -> Vec<u32> {
+    let mut indices = path
+        .read_dir()
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter_map(|entry| {
+                    entry
+                        .file_name()
+                        .to_str()?
+                        .strip_prefix("fan")?
+                        .strip_suffix("_input")?
+                        .parse()
+                        .ok()
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    indices.sort_unstable();
+    indices
+}
+
+pub(super) fn get_fans_from_hwmon(root: &SystemRoot) -> Result<Vec<HwmonFan>, LinuxError> {
+    let mut fans = Vec::new();
+    let path = root.resolve("/sys/class/hwmon");
+
+    if !path.exists() {
+        return Ok(fans);
+    }
+
+    for entry in path.read_dir()? {
+        let entry = entry?;
+        let mut file_path = entry.path();
+        let mut indices = fan_indices(&file_path);
+
+        if indices.is_empty() {
+            file_path = file_path.join("device");
+            indices = fan_indices(&file_path);
+        }
+
+        let chip = std::fs::read_to_string(file_path.join("name"))
+            .ok()
+            .map(|name| name.trim().to_string());
+
+        let device_path = entry.path().join("device");
+        let chip_id = hwmon_id(root, &entry.path(), &device_path, chip.as_deref());
+
+        for index in indices {
+            fans.push(HwmonFan::new(
+                &chip_id,
+                file_path.clone(),
+                device_path.clone(),
+                chip.clone(),
+                index,
+            ));
+        }
+    }
+
+    Ok(fans)
+}
@@ -0,0 +1,204 @@
+//! Identifies the operating system, processor and device from os-release, /proc/cpuinfo, DMI and
+//! the device tree. Every lookup has a fallback, since which of these exist varies wildly between
+//! distributions, architectures and boards.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{CpuTopology, SystemRoot};
+
+/// The part numbers of an ARM CPU implementer's cores, along with their names.
+type ArmParts = &'static [(u32, &'static str)];
+
+/// ARM CPU implementers and the names of their parts, as reported by the `CPU implementer` and
+/// `CPU part` fields of /proc/cpuinfo on ARM systems. This is a subset of the table lscpu uses.
+const ARM_PARTS: &[(u32, &str, ArmParts)] = &[
+    (
+        0x41,
+        "ARM",
+        &[
+            (0xb76, "ARM1176"),
+            (0xc07, "Cortex-A7"),
+            (0xc08, "Cortex-A8"),
+            (0xc09, "Cortex-A9"),
+            (0xc0f, "Cortex-A15"),
+            (0xd03, "Cortex-A53"),
+            (0xd04, "Cortex-A35"),
+            (0xd05, "Cortex-A55"),
+            (0xd07, "Cortex-A57"),
+            (0xd08, "Cortex-A72"),
+            (0xd09, "Cortex-A73"),
+            (0xd0a, "Cortex-A75"),
+            (0xd0b, "Cortex-A76"),
+            (0xd0c, "Neoverse-N1"),
+            (0xd0d, "Cortex-A77"),
+            (0xd40, "Neoverse-V1"),
+            (0xd41, "Cortex-A78"),
+            (0xd44, "Cortex-X1"),
+            (0xd46, "Cortex-A510"),
+            (0xd47, "Cortex-A710"),
+            (0xd48, "Cortex-X2"),
+            (0xd49, "Neoverse-N2"),
+            (0xd4b, "Cortex-A78C"),
+        ],
+    ),
+    (0x42, "Broadcom", &[(0x516, "ThunderX2")]),
+    (0x43, "Cavium", &[(0x0a1, "ThunderX"), (0x0af, "ThunderX2")]),
+    (0x48, "HiSilicon", &[(0xd01, "Kunpeng-920")]),
+    (0x4e, "NVIDIA", &[(0x004, "Carmel")]),
+    (
+        0x51,
+        "Qualcomm",
+        &[
+            (0x800, "Kryo 2XX Gold"),
+            (0x801, "Kryo 2XX Silver"),
+            (0x804, "Kryo 4XX Gold"),
+        ],
+    ),
+    (0x61, "Apple", &[(0x022, "Icestorm"), (0x023, "Firestorm")]),
+    (0xc0, "Ampere", &[(0xac3, "Ampere-1")]),
+];
+
+/// Everything known about the system the sensors are read from.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SystemInfo {
+    /// The name of the distribution, e.g. "Fedora Linux 36 (Workstation Edition)".
+    pub os_name: String,
+    /// The name of the processor, e.g. "AMD Ryzen 9 5900X 12-Core Processor".
+    pub cpu_name: String,
+    /// The model of the device, e.g. "XPS 13 9360" or "Raspberry Pi 4 Model B Rev 1.1".
+    pub device_name: String,
+    /// The kernel release, e.g. "5.19.16-200.fc36.x86_64".
+    pub kernel_version: Option<String>,
+    /// The manufacturer of the motherboard.
+    pub board_vendor: Option<String>,
+    /// The model of the motherboard.
+    pub board_name: Option<String>,
+    /// The version of the BIOS or UEFI firmware.
+    pub bios_version: Option<String>,
+    /// The number of physical cores.
+    pub core_count: Option<usize>,
+    /// The number of logical CPUs, i.e. hardware threads.
+    pub thread_count: Option<usize>,
+}
+
+impl SystemInfo {
+    /// Identifies the system. This never fails: anything that cannot be determined is either
+    /// "Unknown" or `None`.
+    #[must_use]
+    pub fn read(root: &SystemRoot, topology: &CpuTopology) -> Self {
+        let os_release = os_release(root);
+        let cpuinfo = cpuinfo(root);
+        let cpuinfo_key = |key: &str| {
+            cpuinfo
+                .iter()
+                .find_map(|block| block.get(key))
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+
+        let read = |path: &str| {
+            std::fs::read_to_string(root.resolve(path))
+                .ok()
+                // Device tree strings are NUL-terminated.
+                .map(|value| {
+                    value
+                        .trim_matches(|c: char| c.is_whitespace() || c == '\0')
+                        .to_string()
+                })
+                .filter(|value| !value.is_empty())
+        };
+        let dmi = |key: &str| read(&format!("/sys/devices/virtual/dmi/id/{}", key));
+
+        let os_name = ["PRETTY_NAME", "NAME"]
+            .into_iter()
+            .find_map(|key| os_release.get(key).cloned())
+            .unwrap_or_else(|| "Linux".to_string());
+
+        let cpu_name = cpuinfo_key("model name")
+            .or_else(|| arm_cpu_name(cpuinfo_key("CPU implementer")?, cpuinfo_key("CPU part")?))
+            .or_else(|| cpuinfo_key("Hardware"))
+            .or_else(|| cpuinfo_key("cpu model"))
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let device_name = dmi("product_name")
+            .or_else(|| read("/proc/device-tree/model"))
+            .or_else(|| cpuinfo_key("Model"))
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let (core_count, thread_count) = if topology.cpus().is_empty() {
+            let processors = cpuinfo
+                .iter()
+                .filter(|block| block.contains_key("processor"))
+                .count();
+            (None, (processors > 0).then_some(processors))
+        } else {
+            let cores = topology
+                .cpus()
+                .iter()
+                .map(|cpu| (cpu.package, cpu.core))
+                .collect::<HashSet<_>>();
+            (Some(cores.len()), Some(topology.cpus().len()))
+        };
+
+        Self {
+            os_name,
+            cpu_name,
+            device_name,
+            kernel_version: read("/proc/sys/kernel/osrelease"),
+            board_vendor: dmi("board_vendor"),
+            board_name: dmi("board_name"),
+            bios_version: dmi("bios_version"),
+            core_count,
+            thread_count,
+        }
+    }
+}
+
+/// Reads the key-value pairs of os-release, which lives in /etc with a fallback in /usr/lib.
+fn os_release(root: &SystemRoot) -> HashMap<String, String> {
+    ["/etc/os-release", "/usr/lib/os-release"]
+        .into_iter()
+        .find_map(|path| std::fs::read_to_string(root.resolve(path)).ok())
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Reads the blocks of /proc/cpuinfo, which are separated by blank lines. There is one block per
+/// processor, and on some architectures a trailing block describing the whole system.
+fn cpuinfo(root: &SystemRoot) -> Vec<HashMap<String, String>> {
+    std::fs::read_to_string(root.resolve("/proc/cpuinfo"))
+        .unwrap_or_default()
+        .split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .filter_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    Some((key.trim().to_string(), value.trim().to_string()))
+                })
+                .collect::<HashMap<_, _>>()
+        })
+        .filter(|block| !block.is_empty())
+        .collect()
+}
+
+/// Decodes the name of an ARM CPU from its implementer and part number, e.g. `0x41` and `0xd08`
+/// for "ARM Cortex-A72".
+fn arm_cpu_name(implementer: String, part: String) -> Option<String> {
+    let parse = |value: &str| u32::from_str_radix(value.trim_start_matches("0x"), 16).ok();
+    let (implementer, part) = (parse(&implementer)?, parse(&part)?);
+    let (_, vendor, parts) = ARM_PARTS.iter().find(|(id, ..)| *id == implementer)?;
+
+    Some(match parts.iter().find(|(id, _)| *id == part) {
+        Some((_, name)) => format!("{} {}", vendor, name),
+        None => format!("{} CPU part {:#x}", vendor, part),
+    })
+}
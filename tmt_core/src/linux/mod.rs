@@ -0,0 +1,1713 @@
+//! Reads sensors from Linux's hwmon and thermal zone sysfs interfaces.
+
+mod battery;
+mod electrical;
+mod fan;
+mod identity;
+mod nvidia;
+mod sensors_conf;
+mod thermal;
+mod topology;
+mod uevent;
+mod usage;
+
+pub use battery::PowerSupplyBattery;
+pub use fan::{restore_fans, FanControlMode, HwmonFan, MIN_SAFE_DUTY_CYCLE};
+pub use identity::SystemInfo;
+pub use nvidia::{NvidiaGpu, ThrottleReasons, NVIDIA_SMI};
+pub use sensors_conf::{BusType, ChipConfig, ChipName, ConfigError, SensorsConfig};
+pub use thermal::{
+    CoolingBinding, CoolingDevice, ThermalZone, ThermalZoneMode, TripPoint, TripPointType,
+};
+pub use topology::{CpuTopology, LogicalCpu};
+pub use usage::CpuUsage;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use self::uevent::UeventMonitor;
+use self::LinuxError::InvalidData;
+use super::{
+    BatteryState, Component, ComponentType, CpuLocation, Error, ErrorKind, Interface,
+    ReadingStatus, SensorId, SensorReading, Temperature, TemperatureAlarms,
+    TemperatureReading as TemperatureReadingTrait,
+};
+
+/// An error that occured in this module.
+#[derive(Debug)]
+pub enum LinuxError {
+    /// IO error occured when reading from hwmon.
+    IoError(std::io::Error),
+    /// Invalid data received from hwmon.
+    InvalidData(String),
+    /// Refused to set a fan to a speed outside of its safe range.
+    UnsafeFanSpeed,
+}
+
+impl std::error::Error for LinuxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IoError(err) => Some(err),
+            InvalidData(_) | Self::UnsafeFanSpeed => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LinuxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&match self {
+            Self::IoError(err) => format!("io error: {}", err),
+            InvalidData(msg) => format!("invalid data: {}", msg),
+            Self::UnsafeFanSpeed => "unsafe fan speed".to_string(),
+        })
+    }
+}
+
+impl From<std::io::Error> for LinuxError {
+    fn from(err: std::io::Error) -> Self {
+        Self::IoError(err)
+    }
+}
+
+impl From<LinuxError> for Error {
+    fn from(err: LinuxError) -> Self {
+        let kind = match &err {
+            LinuxError::IoError(err) => match err.raw_os_error() {
+                // Reading a device that went away, e.g. an unplugged eGPU, fails with these.
+                Some(libc::ENODEV | libc::ENXIO) => ErrorKind::NotFound,
+                Some(libc::EOPNOTSUPP) => ErrorKind::Unsupported,
+                _ => err.kind().into(),
+            },
+            InvalidData(_) => ErrorKind::InvalidData,
+            LinuxError::UnsafeFanSpeed => ErrorKind::Unsupported,
+        };
+
+        Self::new(kind, err)
+    }
+}
+
+/// The environment variable that, when set, overrides the root directory that sysfs, procfs and
+/// `/etc` are read from. This is useful when running inside of a container with the host's root
+/// mounted elsewhere, e.g. `TMT_ROOT=/host`.
+pub const ROOT_ENV_VAR: &str = "TMT_ROOT";
+
+/// The temperature considered high for sensors that don't report a limit of their own.
+const DEFAULT_HIGH: Temperature = Temperature::from_millidegrees(85_000);
+
+/// The temperature considered critical for sensors that don't report a limit of their own.
+const DEFAULT_CRITICAL: Temperature = Temperature::from_millidegrees(100_000);
+
+/// How often sensors are rescanned by default, so that hot-plugged devices such as docks, eGPUs
+/// and NVMe drives show up without restarting.
+pub const DEFAULT_RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A root directory that every absolute system path read by this module is resolved against.
+#[derive(Clone, Debug)]
+pub struct SystemRoot(PathBuf);
+
+impl SystemRoot {
+    /// Creates a new root at the given directory.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self(path.into())
+    }
+
+    /// Creates a root from the [`ROOT_ENV_VAR`] environment variable, falling back to `/`.
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::new(std::env::var_os(ROOT_ENV_VAR).unwrap_or_else(|| "/".into()))
+    }
+
+    /// The directory this root points to.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Resolves the given absolute system path, e.g. `/sys/class/hwmon`, against this root.
+    #[must_use]
+    pub fn resolve(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        self.0.join(path.strip_prefix("/").unwrap_or(path))
+    }
+}
+
+impl Default for SystemRoot {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// The sensor type read from /sys/class/hwmon/hwmon*/temp*_type
+#[derive(Copy, Clone, Debug)]
+pub enum HwmonSensorType {
+    /// CPU embedded diode
+    CpuDiode,
+    /// 3904 transistor
+    Transistor,
+    /// Thermal diode
+    ThermalDiode,
+    /// Thermistor
+    Thermistor,
+    /// AMD AMDSI
+    Amdsi,
+    /// Intel PECI
+    Peci,
+}
+
+impl HwmonSensorType {
+    /// Returns the sensor type from the given string slice
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim() {
+            "1" => Some(HwmonSensorType::CpuDiode),
+            "2" => Some(HwmonSensorType::Transistor),
+            "3" => Some(HwmonSensorType::ThermalDiode),
+            "4" => Some(HwmonSensorType::Thermistor),
+            "5" => Some(HwmonSensorType::Amdsi),
+            "6" => Some(HwmonSensorType::Peci),
+            _ => None,
+        }
+    }
+}
+
+/// The default classification rules, as `(pattern, component type)` pairs. Patterns are matched
+/// against hwmon chip names, hwmon device drivers and subsystems, and thermal zone types, and may
+/// contain `*` wildcards.
+pub const CLASSIFICATIONS: &[(&str, ComponentType)] = &[
+    // CPUs
+    ("coretemp", ComponentType::Cpu),
+    ("k8temp", ComponentType::Cpu),
+    ("k10temp", ComponentType::Cpu),
+    ("zenpower", ComponentType::Cpu),
+    ("fam15h_power", ComponentType::Cpu),
+    ("via_cputemp", ComponentType::Cpu),
+    ("x86_pkg_temp", ComponentType::Cpu),
+    ("cpu*thermal", ComponentType::Cpu),
+    ("soc*thermal", ComponentType::Cpu),
+    ("TCPU", ComponentType::Cpu),
+    ("B0D4", ComponentType::Cpu),
+    // GPUs
+    ("amdgpu", ComponentType::Gpu),
+    ("radeon", ComponentType::Gpu),
+    ("nouveau", ComponentType::Gpu),
+    ("i915", ComponentType::Gpu),
+    ("xe", ComponentType::Gpu),
+    ("gpu*thermal", ComponentType::Gpu),
+    // Storage
+    ("nvme", ComponentType::Storage),
+    ("drivetemp", ComponentType::Storage),
+    // Memory
+    ("jc42", ComponentType::Memory),
+    ("ee1004", ComponentType::Memory),
+    ("spd5118", ComponentType::Memory),
+    ("ddr*thermal", ComponentType::Memory),
+    // Network
+    ("iwlwifi*", ComponentType::Network),
+    ("ath10k_hwmon", ComponentType::Network),
+    ("ath11k*", ComponentType::Network),
+    ("mt79*", ComponentType::Network),
+    ("r8169*", ComponentType::Network),
+    ("igc*", ComponentType::Network),
+    ("ixgbe*", ComponentType::Network),
+    // Chipsets
+    ("pch_*", ComponentType::Chipset),
+    // Power supplies
+    ("corsairpsu", ComponentType::Psu),
+    ("*psu*", ComponentType::Psu),
+    // Batteries
+    ("BAT*", ComponentType::Battery),
+    ("battery", ComponentType::Battery),
+    ("power_supply", ComponentType::Battery),
+    // Motherboards
+    ("acpitz", ComponentType::Motherboard),
+    ("nct6*", ComponentType::Motherboard),
+    ("nct7*", ComponentType::Motherboard),
+    ("it86*", ComponentType::Motherboard),
+    ("it87*", ComponentType::Motherboard),
+    ("w83*", ComponentType::Motherboard),
+    ("asus*", ComponentType::Motherboard),
+    ("gigabyte_wmi", ComponentType::Motherboard),
+    // System
+    ("dell_smm", ComponentType::System),
+    ("thinkpad", ComponentType::System),
+    ("applesmc", ComponentType::System),
+    ("INT3400 Thermal", ComponentType::System),
+];
+
+/// Matches the given name against a pattern that may contain `*` wildcards.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => name.strip_prefix(prefix).is_some_and(|name| {
+            (0..=name.len())
+                .filter(|&i| name.is_char_boundary(i))
+                .any(|i| matches_pattern(rest, &name[i..]))
+        }),
+    }
+}
+
+/// Classifies Linux sensors into [`ComponentType`]s by their hwmon chip name, driver and subsystem,
+/// or by their thermal zone type.
+#[derive(Clone, Debug)]
+pub struct Classifier {
+    rules: Vec<(String, ComponentType)>,
+}
+
+impl Classifier {
+    /// Adds a rule that classifies sensors matching `pattern` as the given component type. Rules
+    /// that are added later take precedence over earlier ones and over the defaults.
+    pub fn add(&mut self, pattern: impl Into<String>, component_type: ComponentType) {
+        self.rules.insert(0, (pattern.into(), component_type));
+    }
+
+    /// Classifies a sensor known by the given names, e.g. its chip name and driver. The first
+    /// rule that matches any of the names wins, or [`ComponentType::Sensor`] if none do.
+    #[must_use]
+    pub fn classify(&self, names: &[&str]) -> ComponentType {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| names.iter().any(|name| matches_pattern(pattern, name)))
+            .map_or(ComponentType::Sensor, |(_, component_type)| *component_type)
+    }
+}
+
+impl Default for Classifier {
+    fn default() -> Self {
+        Self {
+            rules: CLASSIFICATIONS
+                .iter()
+                .map(|(pattern, component_type)| ((*pattern).to_string(), *component_type))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TemperatureReading {
+    pub id: SensorId,
+    pub name: String,
+    pub temperature: Temperature,
+    pub max: Temperature,
+    pub high: Temperature,
+    pub crit: Temperature,
+    pub min: Option<Temperature>,
+    pub lowest: Option<Temperature>,
+    pub lcrit: Option<Temperature>,
+    pub emergency: Option<Temperature>,
+    pub max_hyst: Option<Temperature>,
+    pub crit_hyst: Option<Temperature>,
+    pub alarms: TemperatureAlarms,
+    pub location: Option<CpuLocation>,
+    pub usage: Option<f32>,
+    pub status: ReadingStatus,
+}
+
+/// Builds the ID of a component from the sysfs interface it is read through, its driver or name,
+/// and the device directory it resolves to, relative to /sys/devices. Unlike the `hwmonN` and
+/// `thermal_zoneN` indices, these don't depend on the order devices were probed in.
+fn component_id(root: &SystemRoot, interface: &str, driver: &str, device: &Path) -> SensorId {
+    let devices = root.resolve("/sys/devices");
+    let devices = devices.canonicalize().unwrap_or(devices);
+    let device = device
+        .canonicalize()
+        .unwrap_or_else(|_| device.to_path_buf());
+    let device = device.strip_prefix(&devices).unwrap_or(&device);
+
+    SensorId::new(format!("{}:{}@{}", interface, driver, device.display()))
+}
+
+/// Builds the ID of a hwmon chip from its driver, or its name if it isn't bound to one, and its
+/// device. Virtual chips without a device are identified by their hwmon directory instead.
+fn hwmon_id(root: &SystemRoot, path: &Path, device_path: &Path, name: Option<&str>) -> SensorId {
+    let driver = std::fs::read_link(device_path.join("driver"))
+        .ok()
+        .and_then(|path| Some(path.file_name()?.to_str()?.to_string()));
+    let driver = driver.as_deref().or(name).unwrap_or("unknown");
+
+    if device_path.exists() {
+        component_id(root, "hwmon", driver, device_path)
+    } else {
+        component_id(root, "hwmon", driver, path)
+    }
+}
+
+/// Whether the given device is asleep: either in a PCI power state other than D0, or
+/// runtime-suspended along with any of its parents, e.g. an NVMe drive behind a suspended PCIe
+/// port. Reading the attributes of such a device resumes it, which keeps it from ever sleeping.
+/// Checking its power state doesn't.
+fn is_asleep(device_path: &Path) -> bool {
+    let power_state = std::fs::read_to_string(device_path.join("power_state"));
+
+    if let Ok(state) = power_state {
+        if !matches!(state.trim(), "D0" | "unknown") {
+            return true;
+        }
+    }
+
+    let Ok(device) = device_path.canonicalize() else {
+        return false;
+    };
+
+    device
+        .ancestors()
+        .take_while(|path| !path.ends_with("sys/devices"))
+        .any(|path| {
+            std::fs::read_to_string(path.join("power/runtime_status"))
+                .ok()
+                .is_some_and(|status| status.trim() == "suspended")
+        })
+}
+
+/// Parses a sysfs temperature, which is in thousandths of a degree Celsius.
+fn parse_temperature(value: &str) -> Option<Temperature> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .map(Temperature::from_millidegrees)
+}
+
+impl TemperatureReadingTrait for TemperatureReading {
+    fn label(&self) -> String {
+        self.name.clone()
+    }
+
+    fn id(&self) -> SensorId {
+        self.id.clone()
+    }
+
+    fn temperature(&self) -> Temperature {
+        self.temperature
+    }
+
+    fn max(&self) -> Temperature {
+        self.max
+    }
+
+    fn high(&self) -> Temperature {
+        self.high
+    }
+
+    fn critical(&self) -> Temperature {
+        self.crit
+    }
+
+    fn min(&self) -> Option<Temperature> {
+        self.min
+    }
+
+    fn lowest(&self) -> Option<Temperature> {
+        self.lowest
+    }
+
+    fn low_critical(&self) -> Option<Temperature> {
+        self.lcrit
+    }
+
+    fn emergency(&self) -> Option<Temperature> {
+        self.emergency
+    }
+
+    fn high_hysteresis(&self) -> Option<Temperature> {
+        self.max_hyst
+    }
+
+    fn critical_hysteresis(&self) -> Option<Temperature> {
+        self.crit_hyst
+    }
+
+    fn alarms(&self) -> TemperatureAlarms {
+        self.alarms
+    }
+
+    fn usage(&self) -> Option<f32> {
+        self.usage
+    }
+
+    fn cpu_location(&self) -> Option<CpuLocation> {
+        self.location.clone()
+    }
+
+    fn status(&self) -> ReadingStatus {
+        self.status.clone()
+    }
+}
+
+pub struct HwmonSensor {
+    id: SensorId,
+    path: PathBuf,
+    device_path: PathBuf,
+    name: Option<String>,
+    driver: Option<String>,
+    subsystem: Option<String>,
+    component_type: ComponentType,
+    update_interval: Duration,
+    last_update: Instant,
+    /// The temperature readings by channel index, i.e. `N` in `tempN_input`.
+    readings: BTreeMap<u32, TemperatureReading>,
+    cpu_locations: HashMap<String, CpuLocation>,
+    usage: Option<f32>,
+    has_temperatures: bool,
+    electrical: Vec<SensorReading>,
+    config: ChipConfig,
+    sensor_type: HwmonSensorType,
+    wait: bool,
+}
+
+impl HwmonSensor {
+    #[must_use]
+    fn new(
+        id: SensorId,
+        path: PathBuf,
+        device_path: PathBuf,
+        name: Option<String>,
+        update_interval: Duration,
+        sensor_type: HwmonSensorType,
+    ) -> Self {
+        let link_name = |link: &str| {
+            std::fs::read_link(device_path.join(link))
+                .ok()
+                .and_then(|path| Some(path.file_name()?.to_str()?.to_string()))
+        };
+
+        Self {
+            driver: link_name("driver"),
+            subsystem: link_name("subsystem"),
+            id,
+            component_type: ComponentType::Sensor,
+            has_temperatures: path.join("temp1_input").exists(),
+            path,
+            device_path,
+            name,
+            update_interval,
+            sensor_type,
+            last_update: Instant::now(),
+            wait: false,
+            readings: BTreeMap::new(),
+            cpu_locations: HashMap::new(),
+            usage: None,
+            electrical: Vec::new(),
+            config: ChipConfig::default(),
+        }
+    }
+
+    fn should_read(&self) -> bool {
+        !self.wait || self.last_update.elapsed() >= self.update_interval
+    }
+
+    /// The name of the chip, or of its device directory if it has no `name` attribute, e.g.
+    /// `0-0018`. Virtual chips without a device are named after their hwmon directory instead.
+    fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+
+        let path = if self.device_path.exists() {
+            &self.device_path
+        } else {
+            &self.path
+        };
+        let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+
+        path.file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+    }
+
+    fn read_temperatures(&mut self) -> Result<(), LinuxError> {
+        if !self.should_read() {
+            return Ok(());
+        }
+
+        // Reading a sleeping device wakes it up, so its readings keep their last known values.
+        if is_asleep(&self.device_path) {
+            self.mark(&ReadingStatus::Sleeping);
+            return Ok(());
+        }
+
+        // A chip that disappeared, e.g. because its driver was unloaded, keeps its last readings
+        // until the next rescan.
+        let entries = match self.path.read_dir() {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.mark(&ReadingStatus::Stale(LinuxError::from(err).to_string()));
+                return Ok(());
+            }
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+
+            let index = name
+                .strip_prefix("temp")
+                .and_then(|name| name.strip_suffix("_input"))
+                .and_then(|index| index.parse::<u32>().ok());
+
+            if let Some(index) = index {
+                let channel = name.trim_end_matches("_input");
+                if self.config.is_ignored(channel) {
+                    self.readings.remove(&index);
+                    continue;
+                }
+
+                let location = self.cpu_locations.get(channel).cloned();
+                let temperature = std::fs::read_to_string(entry.path())
+                    .map_err(LinuxError::from)
+                    .and_then(|temperature| {
+                        parse_temperature(&temperature).ok_or_else(|| {
+                            InvalidData(format!("read invalid temperature {}", temperature.trim()))
+                        })
+                    });
+
+                macro_rules! read {
+                    ($field:literal) => {{
+                        let name = name.replace("_input", concat!("_", $field));
+                        let name = self.path.join(name);
+
+                        std::fs::read_to_string(name).ok()
+                    }};
+                }
+
+                let name = match (self.config.label(channel), &self.name, read!("label")) {
+                    (Some(label), _, _) => label.to_string(),
+                    (None, _, Some(label)) => label.trim().to_string(),
+                    (None, Some(name), None) => name.clone().trim().to_string(),
+                    (None, None, None) => "Unknown".to_string(),
+                };
+
+                // A channel that fails to read, e.g. because of a bus error, keeps its previous
+                // value rather than failing the whole chip.
+                // Values are converted by the channel's `compute` statement, if it has one.
+                let compute = |temperature: Temperature| {
+                    let celsius =
+                        self.config
+                            .compute_in(&self.path, channel, temperature.celsius());
+                    Temperature::from_celsius(celsius)
+                };
+
+                let (temperature, status) = match temperature {
+                    Ok(temperature) => (compute(temperature), ReadingStatus::Ok),
+                    Err(err) => match self.readings.get(&index) {
+                        Some(reading) if reading.status.has_value() => {
+                            (reading.temperature, ReadingStatus::Stale(err.to_string()))
+                        }
+                        _ => (
+                            Temperature::default(),
+                            ReadingStatus::Unavailable(err.to_string()),
+                        ),
+                    },
+                };
+
+                let limit = |value: Option<String>| {
+                    value.as_deref().and_then(parse_temperature).map(compute)
+                };
+                let max = limit(read!("highest")).unwrap_or_default();
+                let high = limit(read!("max")).unwrap_or(DEFAULT_HIGH);
+                let crit = limit(read!("crit")).unwrap_or(DEFAULT_CRITICAL);
+
+                let alarm = |value: Option<String>, alarm| {
+                    if value.as_deref().map(str::trim) == Some("1") {
+                        alarm
+                    } else {
+                        TemperatureAlarms::empty()
+                    }
+                };
+
+                let alarms = alarm(read!("alarm"), TemperatureAlarms::ALARM)
+                    | alarm(read!("min_alarm"), TemperatureAlarms::MIN)
+                    | alarm(read!("max_alarm"), TemperatureAlarms::MAX)
+                    | alarm(read!("lcrit_alarm"), TemperatureAlarms::LOW_CRITICAL)
+                    | alarm(read!("crit_alarm"), TemperatureAlarms::CRITICAL)
+                    | alarm(read!("emergency_alarm"), TemperatureAlarms::EMERGENCY)
+                    | alarm(read!("fault"), TemperatureAlarms::FAULT);
+
+                self.readings.insert(
+                    index,
+                    TemperatureReading {
+                        id: self.id.channel(channel),
+                        name,
+                        temperature,
+                        max,
+                        high,
+                        crit,
+                        min: limit(read!("min")),
+                        lowest: limit(read!("lowest")),
+                        lcrit: limit(read!("lcrit")),
+                        emergency: limit(read!("emergency")),
+                        max_hyst: limit(read!("max_hyst")),
+                        crit_hyst: limit(read!("crit_hyst")),
+                        alarms,
+                        location,
+                        usage: None,
+                        status,
+                    },
+                );
+            }
+        }
+
+        disambiguate_labels(&mut self.readings);
+
+        match electrical::read_channels(&self.path, &self.id, &self.config, &self.electrical) {
+            Ok(electrical) => self.electrical = electrical,
+            Err(err) => self.mark(&ReadingStatus::Stale(err.to_string())),
+        }
+
+        self.last_update = Instant::now();
+        self.wait = true;
+
+        Ok(())
+    }
+
+    /// Gives every reading that still has a value the given status, e.g. stale because of an
+    /// error.
+    fn mark(&mut self, status: &ReadingStatus) {
+        let mark = |current: &mut ReadingStatus| {
+            if current.has_value() {
+                *current = status.clone();
+            }
+        };
+
+        self.readings
+            .values_mut()
+            .for_each(|reading| mark(&mut reading.status));
+        self.electrical
+            .iter_mut()
+            .for_each(|reading| mark(&mut reading.status));
+    }
+
+    /// Resets the recorded lowest and highest temperatures, either through the chip-wide
+    /// `reset_history` attribute or the `tempN_reset_history` attribute of every channel. Chips
+    /// without either are left alone.
+    fn reset_history(&mut self) -> Result<(), LinuxError> {
+        let chip = self.path.join("reset_history");
+
+        if chip.exists() {
+            std::fs::write(chip, "1")?;
+        } else {
+            for entry in self.path.read_dir()?.filter_map(Result::ok) {
+                let is_channel = entry.file_name().to_str().is_some_and(|name| {
+                    name.starts_with("temp") && name.ends_with("_reset_history")
+                });
+
+                if is_channel {
+                    std::fs::write(entry.path(), "1")?;
+                }
+            }
+        }
+
+        // Read the reset values straight away rather than at the next refresh.
+        self.wait = false;
+        self.read_temperatures()
+    }
+}
+
+/// Appends the channel index to every label shared by several readings, e.g. the unlabelled
+/// channels of a chip, which are all named after it, become `dell_smm 1`, `dell_smm 2`, etc.
+fn disambiguate_labels(readings: &mut BTreeMap<u32, TemperatureReading>) {
+    let mut counts = HashMap::<String, usize>::new();
+
+    for reading in readings.values() {
+        *counts.entry(reading.name.clone()).or_default() += 1;
+    }
+
+    for (index, reading) in readings.iter_mut() {
+        if counts[&reading.name] > 1 {
+            reading.name = format!("{} {}", reading.name, index);
+        }
+    }
+}
+
+pub struct ThermalZoneSensor {
+    id: SensorId,
+    path: PathBuf,
+    name: String,
+    component_type: ComponentType,
+    usage: Option<f32>,
+    last_reading: Option<Temperature>,
+    error: Option<String>,
+    max: Temperature,
+    high: Temperature,
+    crit: Temperature,
+}
+
+impl ThermalZoneSensor {
+    fn read_temperature(&mut self) -> Result<(), LinuxError> {
+        let temperature = std::fs::read_to_string(self.path.join("temp"))
+            .map_err(LinuxError::from)
+            .and_then(|temperature| {
+                parse_temperature(&temperature).ok_or_else(|| {
+                    InvalidData(format!("read invalid temperature {}", temperature.trim()))
+                })
+            });
+
+        // Some zones, e.g. those backed by ACPI methods, fail intermittently. The last reading is
+        // kept and reported as stale instead.
+        match temperature {
+            Ok(temperature) => {
+                self.last_reading = Some(temperature);
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+
+        Ok(())
+    }
+
+    fn status(&self) -> ReadingStatus {
+        match (&self.error, self.last_reading) {
+            (None, _) => ReadingStatus::Ok,
+            (Some(err), Some(_)) => ReadingStatus::Stale(err.clone()),
+            (Some(err), None) => ReadingStatus::Unavailable(err.clone()),
+        }
+    }
+}
+
+fn get_sensors_from_hwmon(root: &SystemRoot) -> Result<Vec<HwmonSensor>, LinuxError> {
+    let mut sensors = Vec::new();
+    let path = root.resolve("/sys/class/hwmon");
+
+    if !path.exists() {
+        return Ok(sensors);
+    }
+
+    for entry in path.read_dir()? {
+        let entry = entry?;
+        let mut file_path = entry.path();
+
+        // Check that at least one temperature sensor or electrical channel exists
+        let has_sensors =
+            |path: &Path| path.join("temp1_input").exists() || electrical::has_channels(path);
+
+        if !has_sensors(&file_path) {
+            if has_sensors(&file_path.join("device")) {
+                file_path = file_path.join("device");
+            } else {
+                continue;
+            }
+        }
+
+        let name = std::fs::read_to_string(file_path.join("name"))
+            .ok()
+            .map(|name| name.trim().to_string());
+
+        let update_interval = std::fs::read_to_string(file_path.join("update_interval"))
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or_default();
+
+        let sensor_type = std::fs::read_to_string(file_path.join("temp1_type"))
+            .ok()
+            .and_then(|s| HwmonSensorType::from_str(&s))
+            .unwrap_or(HwmonSensorType::CpuDiode);
+
+        let device_path = entry.path().join("device");
+
+        sensors.push(HwmonSensor::new(
+            hwmon_id(root, &entry.path(), &device_path, name.as_deref()),
+            file_path,
+            device_path,
+            name,
+            update_interval,
+            sensor_type,
+        ));
+    }
+
+    Ok(sensors)
+}
+
+fn get_sensors_from_thermal_zone(root: &SystemRoot) -> Result<Vec<ThermalZoneSensor>, LinuxError> {
+    let mut sensors = Vec::new();
+    let path = root.resolve("/sys/class/thermal");
+
+    if !path.exists() {
+        return Ok(sensors);
+    }
+
+    for entry in path.read_dir()?.filter_map(Result::ok) {
+        if !entry
+            .file_name()
+            .to_str()
+            .is_some_and(|e| e.starts_with("thermal_zone"))
+        {
+            continue;
+        }
+
+        // A zone whose type or trip points can't be read is still read, named after its
+        // directory and with the default thresholds.
+        let name = std::fs::read_to_string(entry.path().join("type"))
+            .map(|name| name.trim().to_string())
+            .unwrap_or_else(|_| entry.file_name().to_string_lossy().into_owned());
+
+        let (mut high, mut critical) = (DEFAULT_HIGH, DEFAULT_CRITICAL);
+
+        for entry in entry
+            .path()
+            .read_dir()
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+        {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+
+            if !name.starts_with("trip_point_") || !name.ends_with("_temp") {
+                continue;
+            }
+
+            // Disabled trip points may report nonsensical values, e.g. iwlwifi reports
+            // -32768000 for all of its trip points while they are unused.
+            let temperature = std::fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|temperature| parse_temperature(&temperature));
+            let temperature = match temperature {
+                Some(temperature) if temperature >= Temperature::ABSOLUTE_ZERO => temperature,
+                _ => continue,
+            };
+
+            let name = name.replace("_temp", "_type");
+            let name = entry.path().parent().unwrap().join(name);
+
+            match std::fs::read_to_string(name)
+                .ok()
+                .unwrap_or_default()
+                .trim()
+            {
+                "critical" => critical = temperature,
+                "hot" => high = temperature,
+                _ => continue,
+            }
+        }
+
+        sensors.push(ThermalZoneSensor {
+            id: component_id(root, "thermal", &name, &entry.path()),
+            path: entry.path(),
+            name,
+            component_type: ComponentType::Sensor,
+            usage: None,
+            last_reading: None,
+            error: None,
+            max: Temperature::default(),
+            high,
+            crit: critical,
+        });
+    }
+
+    Ok(sensors)
+}
+
+/// The sysfs interface a [`LinuxHardwareComponent`] reads its data from.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SensorSource {
+    /// A hwmon chip, from /sys/class/hwmon.
+    Hwmon,
+    /// A thermal zone, from /sys/class/thermal.
+    ThermalZone,
+    /// A power supply, from /sys/class/power_supply.
+    PowerSupply,
+    /// An NVIDIA GPU, read through `nvidia-smi`.
+    NvidiaSmi,
+}
+
+impl std::fmt::Display for SensorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Hwmon => "hwmon",
+            Self::ThermalZone => "thermal zone",
+            Self::PowerSupply => "power supply",
+            Self::NvidiaSmi => "nvidia-smi",
+        })
+    }
+}
+
+pub enum LinuxHardwareComponent {
+    Hwmon(HwmonSensor),
+    ThermalZone(ThermalZoneSensor),
+    Fan(HwmonFan),
+    Battery(PowerSupplyBattery),
+    Nvidia(NvidiaGpu),
+}
+
+impl LinuxHardwareComponent {
+    /// The sysfs interface this component's readings come from.
+    #[must_use]
+    pub const fn source(&self) -> SensorSource {
+        match self {
+            Self::Hwmon(_) | Self::Fan(_) => SensorSource::Hwmon,
+            Self::ThermalZone(_) => SensorSource::ThermalZone,
+            Self::Battery(_) => SensorSource::PowerSupply,
+            Self::Nvidia(_) => SensorSource::NvidiaSmi,
+        }
+    }
+
+    /// The names this component is classified by: the chip name, driver and subsystem for hwmon
+    /// sensors, the zone type for thermal zones, the supply name for batteries, or the driver for
+    /// NVIDIA GPUs.
+    #[must_use]
+    pub fn classification_names(&self) -> Vec<&str> {
+        match self {
+            Self::Hwmon(sensor) => [&sensor.name, &sensor.driver, &sensor.subsystem]
+                .into_iter()
+                .filter_map(Option::as_deref)
+                .collect(),
+            Self::ThermalZone(sensor) => vec![&sensor.name],
+            Self::Fan(fan) => fan.chip().into_iter().collect(),
+            Self::Battery(battery) => vec![battery.name()],
+            Self::Nvidia(_) => vec!["nvidia"],
+        }
+    }
+
+    /// Maps the temperature channels of CPU sensors to the CPUs they measure.
+    fn locate(&mut self, topology: &CpuTopology) {
+        if let Self::Hwmon(sensor) = self {
+            sensor.cpu_locations =
+                topology.locate_channels(sensor.name.as_deref(), &sensor.path, &sensor.device_path);
+        }
+    }
+
+    /// Applies the statements of the lm-sensors configuration that match this component's chip.
+    fn configure(&mut self, root: &SystemRoot, config: &SensorsConfig) {
+        match self {
+            Self::Hwmon(sensor) => {
+                sensor.config = sensor
+                    .name
+                    .as_deref()
+                    .map_or_else(ChipConfig::default, |name| {
+                        config.chip(&ChipName::from_device(root, name, &sensor.device_path))
+                    });
+            }
+            Self::Fan(fan) => {
+                fan.config = fan.chip().map_or_else(ChipConfig::default, |name| {
+                    config.chip(&ChipName::from_device(root, name, &fan.device_path))
+                });
+            }
+            Self::ThermalZone(_) | Self::Battery(_) | Self::Nvidia(_) => (),
+        }
+    }
+
+    /// Whether the lm-sensors configuration ignores this component, which is only possible for
+    /// fans. Ignored temperature and electrical channels are left out of their chip's readings.
+    fn is_ignored(&self) -> bool {
+        self.fan().is_some_and(HwmonFan::is_ignored)
+    }
+
+    fn classify(&mut self, classifier: &Classifier) {
+        let component_type = classifier.classify(&self.classification_names());
+
+        match self {
+            Self::Hwmon(sensor) => sensor.component_type = component_type,
+            Self::ThermalZone(sensor) => sensor.component_type = component_type,
+            // Fans, batteries and GPUs are always what they are, regardless of their names.
+            Self::Fan(_) | Self::Battery(_) | Self::Nvidia(_) => (),
+        }
+    }
+
+    /// Attaches CPU utilisation to this component if it is a CPU, along with each of its readings
+    /// that could be mapped to the CPU topology.
+    fn update_usage(&mut self, usage: &CpuUsage) {
+        if self.component_type() != ComponentType::Cpu {
+            return;
+        }
+
+        match self {
+            Self::Hwmon(sensor) => {
+                let mut cpus = sensor
+                    .cpu_locations
+                    .values()
+                    .flat_map(|location| location.cpus.iter().copied())
+                    .collect::<Vec<_>>();
+                cpus.sort_unstable();
+                cpus.dedup();
+
+                sensor.usage = usage.average(&cpus).or_else(|| usage.total());
+                for reading in sensor.readings.values_mut() {
+                    reading.usage = match &reading.location {
+                        Some(location) => usage.average(&location.cpus),
+                        None => sensor.usage,
+                    };
+                }
+            }
+            Self::ThermalZone(sensor) => sensor.usage = usage.total(),
+            Self::Fan(_) | Self::Battery(_) | Self::Nvidia(_) => (),
+        }
+    }
+
+    /// The fan this component represents, if it is a fan.
+    #[must_use]
+    pub const fn fan(&self) -> Option<&HwmonFan> {
+        match self {
+            Self::Fan(fan) => Some(fan),
+            _ => None,
+        }
+    }
+
+    /// The fan this component represents, if it is a fan. This one returns a mutable reference,
+    /// which can be used to control the fan.
+    pub fn fan_mut(&mut self) -> Option<&mut HwmonFan> {
+        match self {
+            Self::Fan(fan) => Some(fan),
+            _ => None,
+        }
+    }
+
+    /// The battery this component represents, if it is a battery.
+    #[must_use]
+    pub const fn power_supply(&self) -> Option<&PowerSupplyBattery> {
+        match self {
+            Self::Battery(battery) => Some(battery),
+            _ => None,
+        }
+    }
+
+    /// The NVIDIA GPU this component represents, if it is one.
+    #[must_use]
+    pub const fn nvidia(&self) -> Option<&NvidiaGpu> {
+        match self {
+            Self::Nvidia(gpu) => Some(gpu),
+            _ => None,
+        }
+    }
+
+    /// Whether this component is primarily read for its temperatures, as opposed to fans,
+    /// batteries, and chips with only electrical channels.
+    #[must_use]
+    pub fn is_thermal(&self) -> bool {
+        match self {
+            Self::Hwmon(sensor) => sensor.has_temperatures,
+            Self::ThermalZone(_) | Self::Nvidia(_) => true,
+            Self::Fan(_) | Self::Battery(_) => false,
+        }
+    }
+
+    /// The sysfs directory this component's readings are read from, or the device directory of
+    /// NVIDIA GPUs.
+    #[must_use]
+    pub fn sysfs_path(&self) -> &Path {
+        match self {
+            Self::Hwmon(sensor) => &sensor.path,
+            Self::ThermalZone(sensor) => &sensor.path,
+            Self::Fan(fan) => &fan.path,
+            Self::Battery(battery) => &battery.path,
+            Self::Nvidia(gpu) => &gpu.path,
+        }
+    }
+
+    /// What identifies this component across rescans: its source, the device directory it
+    /// resolves to and, for fans, the channel index. The canonical path is used because hwmon
+    /// indices are reused, so `hwmon4` may be a different device after a hot-plug.
+    fn identity(&self) -> (SensorSource, PathBuf, Option<u32>) {
+        let path = self.sysfs_path();
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        (self.source(), path, self.fan().map(HwmonFan::index))
+    }
+}
+
+impl Component for LinuxHardwareComponent {
+    type TemperatureReading = TemperatureReading;
+
+    fn label(&self) -> String {
+        match self {
+            Self::Hwmon(sensor) => sensor.label(),
+            Self::ThermalZone(sensor) => sensor.name.clone(),
+            Self::Fan(fan) => fan.label().to_string(),
+            Self::Battery(battery) => battery.name().to_string(),
+            Self::Nvidia(gpu) => gpu.label(),
+        }
+    }
+
+    fn id(&self) -> SensorId {
+        match self {
+            Self::Hwmon(sensor) => sensor.id.clone(),
+            Self::ThermalZone(sensor) => sensor.id.clone(),
+            Self::Fan(fan) => fan.id().clone(),
+            Self::Battery(battery) => battery.id().clone(),
+            Self::Nvidia(gpu) => gpu.id().clone(),
+        }
+    }
+
+    fn temperatures(&self) -> Vec<Self::TemperatureReading> {
+        match self {
+            Self::Hwmon(sensor) => sensor.readings.values().cloned().collect(),
+            Self::ThermalZone(sensor)
+                if sensor.last_reading.is_none() && sensor.error.is_none() =>
+            {
+                Vec::new()
+            }
+            Self::ThermalZone(sensor) => vec![TemperatureReading {
+                id: sensor.id.channel("temp"),
+                name: sensor.name.clone(),
+                temperature: sensor.last_reading.unwrap_or_default(),
+                max: sensor.max,
+                high: sensor.high,
+                crit: sensor.crit,
+                usage: sensor.usage,
+                status: sensor.status(),
+                ..TemperatureReading::default()
+            }],
+            Self::Fan(_) => Vec::new(),
+            Self::Battery(battery) => battery.temperature().into_iter().collect(),
+            Self::Nvidia(gpu) => gpu.temperatures(),
+        }
+    }
+
+    fn sensors(&self) -> Vec<SensorReading> {
+        match self {
+            Self::Hwmon(sensor) => sensor.electrical.clone(),
+            Self::Nvidia(gpu) => gpu.sensors(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn battery(&self) -> Option<BatteryState> {
+        self.power_supply().map(|battery| battery.state().clone())
+    }
+
+    fn percentage(&self) -> Option<f32> {
+        match self {
+            Self::Hwmon(sensor) => sensor.usage,
+            Self::ThermalZone(sensor) => sensor.usage,
+            Self::Fan(fan) => fan.percentage(),
+            Self::Battery(battery) => battery.capacity(),
+            Self::Nvidia(gpu) => gpu.utilization(),
+        }
+    }
+
+    fn rpm(&self) -> Option<f64> {
+        match self {
+            Self::Fan(fan) => fan.rpm().map(f64::from),
+            _ => None,
+        }
+    }
+
+    fn component_type(&self) -> ComponentType {
+        match self {
+            Self::Hwmon(sensor) => sensor.component_type,
+            Self::ThermalZone(sensor) => sensor.component_type,
+            Self::Fan(_) => ComponentType::Fan,
+            Self::Battery(_) => ComponentType::Battery,
+            Self::Nvidia(_) => ComponentType::Gpu,
+        }
+    }
+
+    fn refresh(&mut self) -> Result<(), Error> {
+        let result = match self {
+            Self::Hwmon(sensor) => sensor.read_temperatures(),
+            Self::ThermalZone(sensor) => sensor.read_temperature(),
+            Self::Fan(fan) => fan.refresh(),
+            Self::Battery(battery) => battery.refresh(),
+            Self::Nvidia(gpu) => {
+                gpu.refresh();
+                Ok(())
+            }
+        };
+
+        result.map_err(|err| Error::from(err).with_component(self.id()))
+    }
+
+    fn reset_history(&mut self) -> Result<(), Error> {
+        let result = match self {
+            Self::Hwmon(sensor) => sensor.reset_history(),
+            _ => Ok(()),
+        };
+
+        result.map_err(|err| Error::from(err).with_component(self.id()))
+    }
+}
+
+/// Whether the given thermal zone is already exposed through one of the given hwmon sensors,
+/// either because the zone registered the hwmon device itself (a `hwmonN` child of the zone), or
+/// because both point to the same underlying device.
+fn is_exposed_through_hwmon(zone: &ThermalZoneSensor, hwmon: &[HwmonSensor]) -> bool {
+    let canonical = |path: &Path| path.canonicalize().ok();
+
+    let chips = hwmon
+        .iter()
+        .filter_map(|sensor| canonical(sensor.device_path.parent()?))
+        .collect::<HashSet<_>>();
+
+    let has_hwmon_child = zone.path.read_dir().is_ok_and(|entries| {
+        entries.filter_map(Result::ok).any(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with("hwmon"))
+                && canonical(&entry.path()).is_some_and(|path| chips.contains(&path))
+        })
+    });
+
+    has_hwmon_child
+        || canonical(&zone.path.join("device")).is_some_and(|device| {
+            hwmon
+                .iter()
+                .any(|sensor| canonical(&sensor.device_path).as_ref() == Some(&device))
+        })
+}
+
+fn get_temperature_sensors(root: &SystemRoot) -> Result<Vec<LinuxHardwareComponent>, LinuxError> {
+    let hwmon = get_sensors_from_hwmon(root)?;
+    let zones = get_sensors_from_thermal_zone(root)?
+        .into_iter()
+        .filter(|zone| !is_exposed_through_hwmon(zone, &hwmon))
+        .collect::<Vec<_>>();
+
+    let fans = fan::get_fans_from_hwmon(root)?;
+    let batteries = battery::get_batteries(root)?;
+    let gpus = nvidia::get_gpus(root)?;
+
+    Ok(hwmon
+        .into_iter()
+        .map(LinuxHardwareComponent::Hwmon)
+        .chain(zones.into_iter().map(LinuxHardwareComponent::ThermalZone))
+        .chain(fans.into_iter().map(LinuxHardwareComponent::Fan))
+        .chain(batteries.into_iter().map(LinuxHardwareComponent::Battery))
+        .chain(gpus.into_iter().map(LinuxHardwareComponent::Nvidia))
+        .collect())
+}
+
+/// Maps the ID of every component to its position. If IDs collide, e.g. because a device registers
+/// several hwmon chips with the same driver, the first component wins.
+fn index(sensors: &[LinuxHardwareComponent]) -> HashMap<SensorId, usize> {
+    let mut index = HashMap::with_capacity(sensors.len());
+
+    for (i, sensor) in sensors.iter().enumerate() {
+        index.entry(sensor.id()).or_insert(i);
+    }
+
+    index
+}
+
+pub struct LinuxComponents {
+    root: SystemRoot,
+    classifier: Classifier,
+    topology: CpuTopology,
+    usage: CpuUsage,
+    sensors: Vec<LinuxHardwareComponent>,
+    /// The position of every component in `sensors` by its ID.
+    index: HashMap<SensorId, usize>,
+    cooling_devices: Vec<CoolingDevice>,
+    thermal_zones: Vec<ThermalZone>,
+    sensors_config: SensorsConfig,
+    info: SystemInfo,
+    rescan_interval: Option<Duration>,
+    nvidia_smi: PathBuf,
+    last_scan: Instant,
+    uevents: Option<UeventMonitor>,
+}
+
+impl LinuxComponents {
+    /// Discovers sensors on this system. Paths are resolved against the directory given by the
+    /// [`ROOT_ENV_VAR`] environment variable if it is set, otherwise against `/`.
+    ///
+    /// Finding no sensors at all, e.g. in a virtual machine or without the coretemp or k10temp
+    /// modules loaded, is not an error.
+    pub fn new() -> Result<Self, Error> {
+        Ok(Self::from_root(SystemRoot::from_env())?)
+    }
+
+    /// Discovers sensors with every system path resolved against the given root directory,
+    /// e.g. the host's root mounted inside of a container, or a captured sysfs tree.
+    pub fn with_root(root: impl Into<PathBuf>) -> Result<Self, Error> {
+        Ok(Self::from_root(SystemRoot::new(root))?)
+    }
+
+    fn from_root(root: SystemRoot) -> Result<Self, LinuxError> {
+        let classifier = Classifier::default();
+        let topology = CpuTopology::read(&root);
+        let sensors_config = SensorsConfig::load(&root);
+        let mut sensors = get_temperature_sensors(&root)?;
+
+        for sensor in &mut sensors {
+            sensor.classify(&classifier);
+            sensor.locate(&topology);
+            sensor.configure(&root, &sensors_config);
+        }
+        sensors.retain(|sensor| !sensor.is_ignored());
+
+        // Uevents only describe the running system, not a captured or mounted tree.
+        let uevents = if root.path() == Path::new("/") {
+            UeventMonitor::new()
+        } else {
+            None
+        };
+
+        Ok(LinuxComponents {
+            info: SystemInfo::read(&root, &topology),
+            usage: CpuUsage::new(&root),
+            index: index(&sensors),
+            cooling_devices: thermal::get_cooling_devices(&root)?,
+            thermal_zones: thermal::get_thermal_zones(&root)?,
+            sensors_config,
+            root,
+            classifier,
+            topology,
+            sensors,
+            rescan_interval: Some(DEFAULT_RESCAN_INTERVAL),
+            nvidia_smi: PathBuf::from(NVIDIA_SMI),
+            last_scan: Instant::now(),
+            uevents,
+        })
+    }
+
+    /// How often sensors are rescanned during [`Interface::refresh`], or `None` if they are only
+    /// rescanned when a device is added or removed, or when [`LinuxComponents::rescan`] is
+    /// called. Defaults to [`DEFAULT_RESCAN_INTERVAL`].
+    #[must_use]
+    pub const fn rescan_interval(&self) -> Option<Duration> {
+        self.rescan_interval
+    }
+
+    /// Sets how often sensors are rescanned during [`Interface::refresh`]. See
+    /// [`LinuxComponents::rescan_interval`].
+    pub fn set_rescan_interval(&mut self, interval: Option<Duration>) {
+        self.rescan_interval = interval;
+    }
+
+    /// The `nvidia-smi` binary NVIDIA GPUs are read with. Defaults to [`NVIDIA_SMI`], which is
+    /// looked up in `PATH`.
+    #[must_use]
+    pub fn nvidia_smi(&self) -> &Path {
+        &self.nvidia_smi
+    }
+
+    /// Sets the `nvidia-smi` binary NVIDIA GPUs are read with, e.g. one that isn't in `PATH`.
+    pub fn set_nvidia_smi(&mut self, path: impl Into<PathBuf>) {
+        self.nvidia_smi = path.into();
+
+        for sensor in &mut self.sensors {
+            if let LinuxHardwareComponent::Nvidia(gpu) = sensor {
+                gpu.set_nvidia_smi(&self.nvidia_smi);
+            }
+        }
+    }
+
+    /// Enumerates sensors again, adding components for devices that appeared and retiring those
+    /// of devices that disappeared. Components that are still present are kept as they are, along
+    /// with their readings and any fan control. Returns whether anything was added or removed.
+    pub fn rescan(&mut self) -> Result<bool, Error> {
+        // Everything is enumerated before anything is replaced, so that a failing scan, e.g. while
+        // a device is being hotplugged, leaves the components and their fan control as they are.
+        let scanned = get_temperature_sensors(&self.root)?;
+        let cooling_devices = thermal::get_cooling_devices(&self.root)?;
+        let thermal_zones = thermal::get_thermal_zones(&self.root)?;
+
+        let mut existing = std::mem::take(&mut self.sensors)
+            .into_iter()
+            .map(|sensor| (sensor.identity(), Some(sensor)))
+            .collect::<HashMap<_, _>>();
+        let mut changed = false;
+
+        for scanned in scanned {
+            let sensor = match existing.get_mut(&scanned.identity()).and_then(Option::take) {
+                // The scanned duplicate is dropped. This doesn't restore fans, since only the
+                // instance that took a fan over restores it on drop.
+                Some(sensor) => sensor,
+                None => {
+                    let mut sensor = scanned;
+                    sensor.classify(&self.classifier);
+                    sensor.locate(&self.topology);
+                    sensor.configure(&self.root, &self.sensors_config);
+                    if sensor.is_ignored() {
+                        continue;
+                    }
+                    if let LinuxHardwareComponent::Nvidia(gpu) = &mut sensor {
+                        gpu.set_nvidia_smi(&self.nvidia_smi);
+                    }
+
+                    changed = true;
+                    sensor
+                }
+            };
+
+            self.sensors.push(sensor);
+        }
+
+        // Whatever is left has disappeared. Retired fans are restored as they are dropped.
+        changed |= existing.into_values().any(|sensor| sensor.is_some());
+        self.index = index(&self.sensors);
+        self.last_scan = Instant::now();
+
+        // Cooling devices and thermal zones hold no state of their own, so they are replaced.
+        changed |= cooling_devices.len() != self.cooling_devices.len()
+            || thermal_zones.len() != self.thermal_zones.len();
+        self.cooling_devices = cooling_devices;
+        self.thermal_zones = thermal_zones;
+
+        Ok(changed)
+    }
+
+    /// Whether sensors should be rescanned before refreshing them: either a device was added or
+    /// removed, a component's directory no longer exists, or the rescan interval has elapsed.
+    fn should_rescan(&self) -> bool {
+        let hotplugged = self.uevents.as_ref().is_some_and(UeventMonitor::poll);
+        let vanished = self
+            .sensors
+            .iter()
+            .any(|sensor| !sensor.sysfs_path().exists());
+        let elapsed = self
+            .rescan_interval
+            .is_some_and(|interval| self.last_scan.elapsed() >= interval);
+
+        hotplugged || vanished || elapsed
+    }
+
+    /// The classifier used to assign component types to sensors.
+    #[must_use]
+    pub fn classifier(&self) -> &Classifier {
+        &self.classifier
+    }
+
+    /// Classifies sensors matching `pattern` as the given component type, taking precedence over
+    /// every existing rule, and reclassifies all sensors. See [`CLASSIFICATIONS`] for how patterns
+    /// are matched.
+    pub fn classify(&mut self, pattern: impl Into<String>, component_type: ComponentType) {
+        self.classifier.add(pattern, component_type);
+
+        for sensor in &mut self.sensors {
+            sensor.classify(&self.classifier);
+        }
+    }
+
+    /// The cooling devices of the kernel's thermal framework, ordered by index.
+    #[must_use]
+    pub fn cooling_devices(&self) -> &[CoolingDevice] {
+        &self.cooling_devices
+    }
+
+    /// The cooling devices of the kernel's thermal framework. This one returns mutable
+    /// references, which can be used to force cooling states.
+    pub fn cooling_devices_mut(&mut self) -> &mut [CoolingDevice] {
+        &mut self.cooling_devices
+    }
+
+    /// Every thermal zone, ordered by index, along with its governor, trip points and bound
+    /// cooling devices. Unlike the thermal zone components, this includes zones that are read
+    /// through hwmon.
+    #[must_use]
+    pub fn thermal_zones(&self) -> &[ThermalZone] {
+        &self.thermal_zones
+    }
+
+    /// Every thermal zone. This one returns mutable references, which can be used to switch
+    /// governors and modes.
+    pub fn thermal_zones_mut(&mut self) -> &mut [ThermalZone] {
+        &mut self.thermal_zones
+    }
+
+    /// The lm-sensors configuration applied to hwmon chips, read from /etc/sensors3.conf and
+    /// /etc/sensors.d. See [`SensorsConfig::load`].
+    #[must_use]
+    pub fn sensors_config(&self) -> &SensorsConfig {
+        &self.sensors_config
+    }
+
+    /// Replaces the lm-sensors configuration applied to hwmon chips, e.g. with one that was read
+    /// from elsewhere, and reapplies it to every sensor. Fans that are no longer ignored show up
+    /// again at the next rescan.
+    pub fn set_sensors_config(&mut self, config: SensorsConfig) {
+        self.sensors_config = config;
+
+        for sensor in &mut self.sensors {
+            sensor.configure(&self.root, &self.sensors_config);
+        }
+
+        self.sensors.retain(|sensor| !sensor.is_ignored());
+        self.index = index(&self.sensors);
+    }
+
+    /// Writes the limits given by the `set` statements of the lm-sensors configuration to every
+    /// hwmon chip, like `sensors -s` does.
+    pub fn apply_sensors_config_limits(&self) -> Result<(), Error> {
+        for sensor in &self.sensors {
+            if let LinuxHardwareComponent::Hwmon(sensor) = sensor {
+                sensor
+                    .config
+                    .apply_limits(&sensor.path)
+                    .map_err(|err| Error::from(err).with_component(sensor.id.clone()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The topology of the CPUs that CPU temperature readings are mapped to.
+    #[must_use]
+    pub fn topology(&self) -> &CpuTopology {
+        &self.topology
+    }
+
+    /// Everything known about the system, beyond what [`Interface`] exposes.
+    #[must_use]
+    pub fn info(&self) -> &SystemInfo {
+        &self.info
+    }
+
+    /// The utilisation of every CPU as of the last refresh.
+    #[must_use]
+    pub fn usage(&self) -> &CpuUsage {
+        &self.usage
+    }
+
+    /// The root directory system paths are resolved against.
+    #[must_use]
+    pub fn root(&self) -> &SystemRoot {
+        &self.root
+    }
+}
+
+impl Interface for LinuxComponents {
+    type Component = LinuxHardwareComponent;
+
+    fn thermal_components(&self) -> Vec<&Self::Component> {
+        self.sensors.iter().filter(|c| c.is_thermal()).collect()
+    }
+
+    fn thermal_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        self.sensors.iter_mut().filter(|c| c.is_thermal()).collect()
+    }
+
+    fn sensor_components(&self) -> Vec<&Self::Component> {
+        self.sensors
+            .iter()
+            .filter(|c| !c.sensors().is_empty())
+            .collect()
+    }
+
+    fn fan_components(&self) -> Vec<&Self::Component> {
+        self.sensors.iter().filter(|c| c.fan().is_some()).collect()
+    }
+
+    fn fan_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        self.sensors
+            .iter_mut()
+            .filter(|c| c.fan().is_some())
+            .collect()
+    }
+
+    fn battery_components(&self) -> Vec<&Self::Component> {
+        self.sensors
+            .iter()
+            .filter(|c| c.power_supply().is_some())
+            .collect()
+    }
+
+    fn battery_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        self.sensors
+            .iter_mut()
+            .filter(|c| c.power_supply().is_some())
+            .collect()
+    }
+
+    fn component_by_id(&self, id: &SensorId) -> Option<&Self::Component> {
+        self.index
+            .get(id)
+            .or_else(|| self.index.get(&id.component()))
+            .map(|&i| &self.sensors[i])
+    }
+
+    fn os_name(&self) -> String {
+        self.info.os_name.clone()
+    }
+
+    fn cpu_name(&self) -> String {
+        self.info.cpu_name.clone()
+    }
+
+    fn gpu_name(&self) -> Option<String> {
+        self.sensors
+            .iter()
+            .find_map(|sensor| sensor.nvidia()?.name())
+            .map(str::to_string)
+    }
+
+    fn device_model_name(&self) -> String {
+        self.info.device_name.clone()
+    }
+
+    fn kernel_version(&self) -> Option<String> {
+        self.info.kernel_version.clone()
+    }
+
+    fn board_vendor(&self) -> Option<String> {
+        self.info.board_vendor.clone()
+    }
+
+    fn board_name(&self) -> Option<String> {
+        self.info.board_name.clone()
+    }
+
+    fn bios_version(&self) -> Option<String> {
+        self.info.bios_version.clone()
+    }
+
+    fn core_count(&self) -> Option<usize> {
+        self.info.core_count
+    }
+
+    fn thread_count(&self) -> Option<usize> {
+        self.info.thread_count
+    }
+
+    fn refresh(&mut self) -> Result<(), Error> {
+        // Some hwmon chips only have electrical channels, so they are neither thermal nor fan
+        // components but still need to be refreshed. A component that fails to refresh doesn't
+        // stop the others from updating; the first error is returned once all are done.
+        let mut result = Ok(());
+
+        if self.should_rescan() {
+            if let Err(err) = self.rescan() {
+                result = Err(err);
+            }
+        }
+
+        // nvidia-smi is slow to start, so it is run once for every NVIDIA GPU that is awake.
+        let mut nvidia = None;
+
+        for sensor in &mut self.sensors {
+            match sensor {
+                LinuxHardwareComponent::Nvidia(gpu) if !gpu.is_asleep() => {
+                    gpu.update(
+                        nvidia
+                            .get_or_insert_with(|| nvidia::query(&self.nvidia_smi))
+                            .as_deref(),
+                    );
+                }
+                sensor => {
+                    if let Err(err) = sensor.refresh() {
+                        result = result.and(Err(err));
+                    }
+                }
+            }
+        }
+
+        for device in &mut self.cooling_devices {
+            device.refresh();
+        }
+
+        for zone in &mut self.thermal_zones {
+            zone.refresh();
+        }
+
+        if let Err(err) = self.usage.refresh(&self.root) {
+            result = result.and(Err(Error::from(err)));
+        }
+        for sensor in &mut self.sensors {
+            sensor.update_usage(&self.usage);
+        }
+
+        result
+    }
+}
+
+/// Discovers sensors like [`LinuxComponents::new`], panicking if that fails.
+impl Default for LinuxComponents {
+    fn default() -> Self {
+        Self::new().unwrap()
+    }
+}
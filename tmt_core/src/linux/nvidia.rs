@@ -0,0 +1,364 @@
+//! NVIDIA GPUs driven by the proprietary driver, which exposes no hwmon chip.
+//!
+//! GPUs are discovered through the PCI devices bound to the `nvidia` driver, and read by running
+//! `nvidia-smi` in query mode, so it is never run on systems without one. A missing or failing
+//! `nvidia-smi` leaves the GPUs stale rather than failing the refresh.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{
+    component_id, is_asleep, LinuxError, LinuxError::InvalidData, SystemRoot, TemperatureReading,
+    DEFAULT_CRITICAL, DEFAULT_HIGH,
+};
+use crate::{ReadingStatus, SensorId, SensorKind, SensorReading, Temperature, TemperatureAlarms};
+
+/// The `nvidia-smi` binary, which is looked up in `PATH` unless another one is set with
+/// [`LinuxComponents::set_nvidia_smi`](super::LinuxComponents::set_nvidia_smi).
+pub const NVIDIA_SMI: &str = "nvidia-smi";
+
+/// The fields queried from `nvidia-smi`, in the order they are parsed in.
+const QUERY_FIELDS: &str = "pci.bus_id,name,temperature.gpu,temperature.memory,fan.speed,\
+    power.draw,power.limit,utilization.gpu,utilization.memory,clocks_throttle_reasons.active";
+
+bitflags::bitflags! {
+    /// Why the clocks of an NVIDIA GPU are held back, from `clocks_throttle_reasons.active`.
+    #[derive(Default)]
+    pub struct ThrottleReasons: u64 {
+        /// Nothing is running on the GPU.
+        const GPU_IDLE = 0x1;
+        /// The clocks are limited by the application clocks setting.
+        const APPLICATIONS_CLOCKS_SETTING = 0x2;
+        /// The GPU is drawing as much power as its power limit allows.
+        const SW_POWER_CAP = 0x4;
+        /// The hardware is slowing the GPU down, because of temperature or power.
+        const HW_SLOWDOWN = 0x8;
+        /// The GPU is held back to match the other GPUs of its sync boost group.
+        const SYNC_BOOST = 0x10;
+        /// The driver is slowing the GPU down to keep it below its maximum temperature.
+        const SW_THERMAL_SLOWDOWN = 0x20;
+        /// The hardware is slowing the GPU down because it is too hot.
+        const HW_THERMAL_SLOWDOWN = 0x40;
+        /// The power supply asserted the power brake signal.
+        const HW_POWER_BRAKE_SLOWDOWN = 0x80;
+        /// The clocks are limited by the display clock setting.
+        const DISPLAY_CLOCK_SETTING = 0x100;
+    }
+}
+
+impl ThrottleReasons {
+    /// Whether the GPU is held back because it is too hot.
+    #[must_use]
+    pub const fn is_thermal(self) -> bool {
+        self.contains(Self::SW_THERMAL_SLOWDOWN) || self.contains(Self::HW_THERMAL_SLOWDOWN)
+    }
+}
+
+/// The values of a single GPU, from one line of `nvidia-smi` output. Values the GPU doesn't
+/// support are reported as `[N/A]` or `[Not Supported]`, and are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct GpuQuery {
+    bus_id: String,
+    name: String,
+    temperature: Option<Temperature>,
+    memory_temperature: Option<Temperature>,
+    fan_speed: Option<f32>,
+    power_draw: Option<f64>,
+    power_limit: Option<f64>,
+    utilization: Option<f32>,
+    memory_utilization: Option<f32>,
+    throttle_reasons: ThrottleReasons,
+}
+
+impl GpuQuery {
+    fn parse(line: &str) -> Result<Self, LinuxError> {
+        let mut fields = line.split(',').map(str::trim);
+        let mut field = || {
+            fields
+                .next()
+                .ok_or_else(|| InvalidData(format!("unexpected {} output: {}", NVIDIA_SMI, line)))
+        };
+        let celsius = |value: &str| value.parse().ok().map(Temperature::from_celsius);
+
+        Ok(Self {
+            bus_id: normalize_bus_id(field()?),
+            name: field()?.to_string(),
+            temperature: celsius(field()?),
+            memory_temperature: celsius(field()?),
+            fan_speed: field()?.parse().ok(),
+            power_draw: field()?.parse().ok(),
+            power_limit: field()?.parse().ok(),
+            utilization: field()?.parse().ok(),
+            memory_utilization: field()?.parse().ok(),
+            throttle_reasons: u64::from_str_radix(field()?.trim_start_matches("0x"), 16)
+                .map(ThrottleReasons::from_bits_truncate)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Converts a PCI bus ID as printed by `nvidia-smi`, e.g. `00000000:0A:00.0`, to the form sysfs
+/// uses, e.g. `0000:0a:00.0`.
+fn normalize_bus_id(bus_id: &str) -> String {
+    let bus_id = bus_id.to_ascii_lowercase();
+
+    match bus_id.split_once(':') {
+        Some((domain, rest)) => match u32::from_str_radix(domain, 16) {
+            Ok(domain) => format!("{:04x}:{}", domain, rest),
+            Err(_) => bus_id,
+        },
+        None => bus_id,
+    }
+}
+
+/// Runs the given `nvidia-smi` once, returning the values of every GPU it knows about.
+pub(super) fn query(nvidia_smi: &Path) -> Result<Vec<GpuQuery>, LinuxError> {
+    let output = Command::new(nvidia_smi)
+        .arg(format!("--query-gpu={}", QUERY_FIELDS))
+        .arg("--format=csv,noheader,nounits")
+        .output()
+        .map_err(|err| {
+            std::io::Error::new(err.kind(), format!("failed to run {}: {}", NVIDIA_SMI, err))
+        })?;
+
+    // nvidia-smi prints most of its errors, e.g. when it can't reach the driver, to stdout.
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr
+            .lines()
+            .chain(stdout.lines())
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+
+        return Err(InvalidData(format!(
+            "{} failed ({}): {}",
+            NVIDIA_SMI, output.status, message
+        )));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(GpuQuery::parse)
+        .collect()
+}
+
+/// An NVIDIA GPU, read through `nvidia-smi`.
+pub struct NvidiaGpu {
+    id: SensorId,
+    pub(super) path: PathBuf,
+    bus_id: String,
+    /// The values of the last successful query.
+    last: Option<GpuQuery>,
+    error: Option<String>,
+    sleeping: bool,
+    /// The `nvidia-smi` binary the GPU is read with when it is refreshed on its own.
+    nvidia_smi: PathBuf,
+}
+
+impl NvidiaGpu {
+    fn new(id: SensorId, path: PathBuf, bus_id: String) -> Self {
+        Self {
+            id,
+            path,
+            bus_id,
+            last: None,
+            error: None,
+            sleeping: false,
+            nvidia_smi: PathBuf::from(NVIDIA_SMI),
+        }
+    }
+
+    /// The stable ID of the GPU.
+    #[must_use]
+    pub const fn id(&self) -> &SensorId {
+        &self.id
+    }
+
+    /// The PCI address of the GPU, e.g. `0000:01:00.0`.
+    #[must_use]
+    pub fn bus_id(&self) -> &str {
+        &self.bus_id
+    }
+
+    /// The name of the GPU, e.g. `NVIDIA GeForce RTX 4090`, once it has been read.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.last.as_ref().map(|last| last.name.as_str())
+    }
+
+    /// The speed of the GPU's fan, as a percentage of its maximum speed from 0.0 to 100.0, if it
+    /// has one.
+    #[must_use]
+    pub fn fan_speed(&self) -> Option<f32> {
+        self.last.as_ref()?.fan_speed
+    }
+
+    /// The power drawn by the GPU, in watts.
+    #[must_use]
+    pub fn power_draw(&self) -> Option<f64> {
+        self.last.as_ref()?.power_draw
+    }
+
+    /// The power limit of the GPU, in watts.
+    #[must_use]
+    pub fn power_limit(&self) -> Option<f64> {
+        self.last.as_ref()?.power_limit
+    }
+
+    /// The utilisation of the GPU, from 0.0 to 100.0.
+    #[must_use]
+    pub fn utilization(&self) -> Option<f32> {
+        self.last.as_ref()?.utilization
+    }
+
+    /// The utilisation of the GPU's memory bandwidth, from 0.0 to 100.0.
+    #[must_use]
+    pub fn memory_utilization(&self) -> Option<f32> {
+        self.last.as_ref()?.memory_utilization
+    }
+
+    /// Why the GPU's clocks are currently held back.
+    #[must_use]
+    pub fn throttle_reasons(&self) -> ThrottleReasons {
+        self.last
+            .as_ref()
+            .map(|last| last.throttle_reasons)
+            .unwrap_or_default()
+    }
+
+    /// The label of the GPU: its name, or `nvidia` until it has been read.
+    pub(super) fn label(&self) -> String {
+        self.name().unwrap_or("nvidia").to_string()
+    }
+
+    fn status(&self) -> ReadingStatus {
+        match (&self.error, &self.last) {
+            (_, Some(_)) if self.sleeping => ReadingStatus::Sleeping,
+            (None, _) => ReadingStatus::Ok,
+            (Some(err), Some(_)) => ReadingStatus::Stale(err.clone()),
+            (Some(err), None) => ReadingStatus::Unavailable(err.clone()),
+        }
+    }
+
+    /// The core and, if the GPU reports it, memory temperature.
+    pub(super) fn temperatures(&self) -> Vec<TemperatureReading> {
+        let reading = |channel, name: &str, temperature: Temperature, usage| TemperatureReading {
+            id: self.id.channel(channel),
+            name: name.to_string(),
+            temperature,
+            high: DEFAULT_HIGH,
+            crit: DEFAULT_CRITICAL,
+            usage,
+            status: self.status(),
+            ..TemperatureReading::default()
+        };
+
+        let Some(last) = &self.last else {
+            return match &self.error {
+                Some(_) => vec![reading("gpu", "GPU", Temperature::default(), None)],
+                None => Vec::new(),
+            };
+        };
+
+        let mut readings = Vec::new();
+        if let Some(temperature) = last.temperature {
+            let mut gpu = reading("gpu", "GPU", temperature, last.utilization);
+            if last.throttle_reasons.is_thermal() {
+                gpu.alarms = TemperatureAlarms::MAX;
+            }
+            readings.push(gpu);
+        }
+        if let Some(temperature) = last.memory_temperature {
+            readings.push(reading(
+                "memory",
+                "Memory",
+                temperature,
+                last.memory_utilization,
+            ));
+        }
+
+        readings
+    }
+
+    /// The power draw of the GPU, along with its power limit.
+    pub(super) fn sensors(&self) -> Vec<SensorReading> {
+        self.power_draw()
+            .map(|power| SensorReading {
+                id: self.id.channel("power"),
+                label: "Power".to_string(),
+                kind: SensorKind::Power,
+                value: power,
+                min: None,
+                max: self.power_limit(),
+                status: self.status(),
+            })
+            .into_iter()
+            .collect()
+    }
+
+    /// Whether the GPU is asleep, in which case running `nvidia-smi` would wake it up.
+    pub(super) fn is_asleep(&self) -> bool {
+        is_asleep(&self.path)
+    }
+
+    /// Updates the GPU from the result of a query of every GPU.
+    pub(super) fn update(&mut self, query: Result<&[GpuQuery], &LinuxError>) {
+        self.sleeping = false;
+
+        match query.map(|gpus| gpus.iter().find(|gpu| gpu.bus_id == self.bus_id)) {
+            Ok(Some(gpu)) => {
+                self.last = Some(gpu.clone());
+                self.error = None;
+            }
+            Ok(None) => {
+                self.error = Some(format!("{} did not report {}", NVIDIA_SMI, self.bus_id));
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// Queries this GPU alone. [`LinuxComponents`](super::LinuxComponents) refreshes every GPU
+    /// with a single query instead, as `nvidia-smi` is slow to start.
+    pub(super) fn refresh(&mut self) {
+        if self.is_asleep() {
+            self.sleeping = true;
+        } else {
+            self.update(query(&self.nvidia_smi).as_deref());
+        }
+    }
+
+    pub(super) fn set_nvidia_smi(&mut self, nvidia_smi: &Path) {
+        self.nvidia_smi = nvidia_smi.to_path_buf();
+    }
+}
+
+/// Returns every GPU bound to the `nvidia` driver, ordered by PCI address.
+pub(super) fn get_gpus(root: &SystemRoot) -> Result<Vec<NvidiaGpu>, LinuxError> {
+    let mut gpus = Vec::new();
+    let path = root.resolve("/sys/bus/pci/drivers/nvidia");
+
+    if !path.exists() {
+        return Ok(gpus);
+    }
+
+    for entry in path.read_dir()? {
+        let entry = entry?;
+        let bus_id = entry.file_name().to_string_lossy().into_owned();
+
+        // Besides links to the devices it is bound to, the driver's directory holds attributes
+        // such as `bind` and `new_id`.
+        if !bus_id.contains(':') {
+            continue;
+        }
+
+        let path = entry.path().canonicalize().unwrap_or_else(|_| entry.path());
+        let id = component_id(root, "nvidia-smi", "nvidia", &path);
+        gpus.push(NvidiaGpu::new(id, path, bus_id));
+    }
+
+    gpus.sort_by(|a, b| a.bus_id.cmp(&b.bus_id));
+    Ok(gpus)
+}
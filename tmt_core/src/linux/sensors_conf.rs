@@ -0,0 +1,854 @@
+//! lm-sensors configuration, i.e. /etc/sensors3.conf and /etc/sensors.d/*, which labels, hides
+//! and rescales the channels of hwmon chips the same way `sensors` does.
+//!
+//! Chips are matched by their lm-sensors names, e.g. `nct6798-isa-0290` or `jc42-i2c-0-18`,
+//! which are derived from the device each chip belongs to.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::{LinuxError, SystemRoot};
+
+/// The bus a hwmon chip is attached to, as named by lm-sensors.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BusType {
+    /// An ISA or platform device, such as a Super I/O chip or coretemp.
+    Isa,
+    /// A PCI device, such as k10temp or a GPU.
+    Pci,
+    /// An I2C or SMBus device, such as a DIMM temperature sensor.
+    I2c,
+    /// An SPI device, numbered after its bus and chip select.
+    Spi,
+    /// A device that doesn't sit on any bus, such as a thermal zone without an ACPI device.
+    Virtual,
+    /// An ACPI device, such as acpitz or a battery.
+    Acpi,
+    /// A HID device, such as a USB fan controller or power supply.
+    Hid,
+    /// An MDIO device, such as an Ethernet PHY.
+    Mdio,
+    /// A SCSI device, such as a drive monitored by drivetemp.
+    Scsi,
+}
+
+/// The name lm-sensors gives a hwmon chip, e.g. `nct6798-isa-0290`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChipName {
+    /// The name of the chip, from its hwmon `name` attribute.
+    pub prefix: String,
+    /// The bus the chip is attached to.
+    pub bus: BusType,
+    /// The number of the I2C or SPI bus, HID bus or SCSI host the chip is attached to, or 0 for
+    /// other buses.
+    pub bus_number: u32,
+    /// The address of the chip on its bus.
+    pub address: u32,
+    /// The name of the I2C adapter the chip is attached to, which `bus` statements refer to.
+    pub adapter: Option<String>,
+}
+
+impl ChipName {
+    /// Derives the name of a hwmon chip from the device it belongs to: the closest I2C client,
+    /// PCI function, SPI, HID, SCSI, MDIO, ACPI or platform device in its device path.
+    pub(super) fn from_device(root: &SystemRoot, prefix: &str, device_path: &Path) -> Self {
+        let mut name = Self {
+            prefix: prefix.to_string(),
+            bus: BusType::Virtual,
+            bus_number: 0,
+            address: 0,
+            adapter: None,
+        };
+
+        let devices = root.resolve("/sys/devices");
+        let devices = devices.canonicalize().unwrap_or(devices);
+        let Ok(device) = device_path.canonicalize() else {
+            return name;
+        };
+        let Ok(relative) = device.strip_prefix(&devices) else {
+            return name;
+        };
+
+        for path in relative.ancestors() {
+            let Some(device) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let parent = path.parent().and_then(Path::to_str).unwrap_or_default();
+
+            if let Some((bus, address)) = parse_i2c_client(device) {
+                name.bus = BusType::I2c;
+                name.bus_number = bus;
+                name.address = address;
+                name.adapter = std::fs::read_to_string(devices.join(parent).join("name"))
+                    .ok()
+                    .map(|adapter| adapter.trim().to_string());
+                break;
+            } else if let Some(address) = parse_pci_address(device) {
+                name.bus = BusType::Pci;
+                name.address = address;
+                break;
+            } else if let Some((bus, address)) = parse_spi_device(device) {
+                name.bus = BusType::Spi;
+                name.bus_number = bus;
+                name.address = address;
+                break;
+            } else if let Some((bus, address)) = parse_hid_device(device) {
+                name.bus = BusType::Hid;
+                name.bus_number = bus;
+                name.address = address;
+                break;
+            } else if let Some((host, address)) = parse_scsi_device(device) {
+                name.bus = BusType::Scsi;
+                name.bus_number = host;
+                name.address = address;
+                break;
+            } else if Path::new(parent)
+                .parent()
+                .is_some_and(|bus| bus.ends_with("mdio_bus"))
+            {
+                // MDIO devices are named after their bus and address, e.g. stmmac-0:01.
+                name.bus = BusType::Mdio;
+                name.address = device
+                    .rsplit_once(':')
+                    .and_then(|(_, address)| u32::from_str_radix(address, 16).ok())
+                    .unwrap_or(0);
+                break;
+            } else if is_acpi_device(device) {
+                name.bus = BusType::Acpi;
+                break;
+            } else if parent == "platform" {
+                // Platform devices are numbered after their ISA address, e.g. nct6775.656.
+                name.bus = BusType::Isa;
+                name.address = device
+                    .rsplit_once('.')
+                    .and_then(|(_, address)| address.parse().ok())
+                    .unwrap_or(0);
+                break;
+            }
+        }
+
+        name
+    }
+}
+
+impl std::fmt::Display for ChipName {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.bus {
+            BusType::Isa => write!(f, "{}-isa-{:04x}", self.prefix, self.address),
+            BusType::Pci => write!(f, "{}-pci-{:04x}", self.prefix, self.address),
+            BusType::I2c => write!(
+                f,
+                "{}-i2c-{}-{:02x}",
+                self.prefix, self.bus_number, self.address
+            ),
+            BusType::Spi => write!(
+                f,
+                "{}-spi-{}-{:x}",
+                self.prefix, self.bus_number, self.address
+            ),
+            BusType::Virtual => write!(f, "{}-virtual-{:x}", self.prefix, self.address),
+            BusType::Acpi => write!(f, "{}-acpi-{:x}", self.prefix, self.address),
+            BusType::Hid => write!(
+                f,
+                "{}-hid-{}-{:x}",
+                self.prefix, self.bus_number, self.address
+            ),
+            BusType::Mdio => write!(f, "{}-mdio-{:x}", self.prefix, self.address),
+            BusType::Scsi => write!(
+                f,
+                "{}-scsi-{}-{:x}",
+                self.prefix, self.bus_number, self.address
+            ),
+        }
+    }
+}
+
+/// Parses the name of an I2C client device, e.g. `0-0018`, into its bus number and address.
+fn parse_i2c_client(name: &str) -> Option<(u32, u32)> {
+    let (bus, address) = name.split_once('-')?;
+
+    if address.len() != 4 {
+        return None;
+    }
+
+    Some((bus.parse().ok()?, u32::from_str_radix(address, 16).ok()?))
+}
+
+/// Parses the name of an SPI device, e.g. `spi0.1`, into its bus number and chip select.
+fn parse_spi_device(name: &str) -> Option<(u32, u32)> {
+    let (bus, chip_select) = name.strip_prefix("spi")?.split_once('.')?;
+
+    Some((bus.parse().ok()?, chip_select.parse().ok()?))
+}
+
+/// Parses the name of a HID device, e.g. `0003:046D:C52B.0001`, into its bus type and instance.
+fn parse_hid_device(name: &str) -> Option<(u32, u32)> {
+    let hex = |part: &str| {
+        (part.len() == 4)
+            .then(|| u32::from_str_radix(part, 16).ok())
+            .flatten()
+    };
+    let (ids, instance) = name.split_once('.')?;
+    let ids = ids.split(':').map(hex).collect::<Option<Vec<_>>>()?;
+
+    match ids[..] {
+        [bus, _vendor, _product] => Some((bus, hex(instance)?)),
+        _ => None,
+    }
+}
+
+/// Parses the name of a SCSI device, e.g. `2:0:0:0`, into its host number and the address
+/// lm-sensors names the chip by.
+fn parse_scsi_device(name: &str) -> Option<(u32, u32)> {
+    let parts = name
+        .split(':')
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    match parts[..] {
+        [host, _channel, id, lun] => Some((host, (id << 8) + lun)),
+        _ => None,
+    }
+}
+
+/// Whether the name is the one of an ACPI device, e.g. `LNXTHERM:00` or `PNP0C0A:00`.
+fn is_acpi_device(name: &str) -> bool {
+    name.split_once(':').is_some_and(|(id, instance)| {
+        matches!(id.len(), 7 | 8)
+            && id
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            && instance.len() == 2
+            && instance.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Parses a PCI address, e.g. `0000:00:18.3`, into the address lm-sensors names the chip by.
+fn parse_pci_address(name: &str) -> Option<u32> {
+    let mut parts = name.split([':', '.']);
+    let mut next = |len: usize| {
+        let part = parts.next().filter(|part| part.len() == len)?;
+        u32::from_str_radix(part, 16).ok()
+    };
+
+    let (domain, bus, slot, function) = (next(4)?, next(2)?, next(2)?, next(1)?);
+    parts
+        .next()
+        .is_none()
+        .then_some((domain << 16) | (bus << 8) | (slot << 3) | function)
+}
+
+/// An error in an lm-sensors configuration file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigError {
+    /// The line the error is on, starting from 1.
+    pub line: usize,
+    /// What is wrong.
+    pub message: String,
+}
+
+impl std::error::Error for ConfigError {}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    String(String),
+    Number(f64),
+    Symbol(char),
+    Newline,
+}
+
+/// Splits a configuration file into tokens, along with the line each is on. Comments are dropped
+/// and escaped newlines join lines, while other newlines end statements.
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ConfigError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+    let error = |line, message: String| ConfigError { line, message };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                tokens.push((line, Token::Newline));
+                line += 1;
+            }
+            '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '\\' if chars.peek() == Some(&'\n') => {
+                chars.next();
+                line += 1;
+            }
+            c if c.is_whitespace() => (),
+            '"' => {
+                let mut string = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => string.push('\n'),
+                            Some('t') => string.push('\t'),
+                            Some(c) if c != '\n' => string.push(c),
+                            _ => return Err(error(line, "unterminated string".to_string())),
+                        },
+                        Some('\n') | None => {
+                            return Err(error(line, "unterminated string".to_string()))
+                        }
+                        Some(c) => string.push(c),
+                    }
+                }
+
+                tokens.push((line, Token::String(string)));
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                }
+
+                let number = number
+                    .parse()
+                    .map_err(|_| error(line, format!("invalid number {}", number)))?;
+                tokens.push((line, Token::Number(number)));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+
+                tokens.push((line, Token::Name(name)));
+            }
+            '+' | '-' | '*' | '/' | '(' | ')' | ',' | '@' | '^' | '`' => {
+                tokens.push((line, Token::Symbol(c)));
+            }
+            c => return Err(error(line, format!("unexpected character {:?}", c))),
+        }
+    }
+
+    tokens.push((line, Token::Newline));
+    Ok(tokens)
+}
+
+/// An arithmetic expression of a `compute` or `set` statement.
+#[derive(Clone, Debug, PartialEq)]
+enum Expression {
+    Number(f64),
+    /// `@`, the value being converted.
+    Value,
+    /// The raw value of another channel of the chip, e.g. `in0`.
+    Channel(String),
+    Negate(Box<Expression>),
+    /// `^x`, i.e. e to the power of x.
+    Exp(Box<Expression>),
+    /// `` `x ``, i.e. the natural logarithm of x.
+    Ln(Box<Expression>),
+    Binary(Box<Expression>, char, Box<Expression>),
+}
+
+impl Expression {
+    /// Evaluates the expression with `@` set to the given value. Returns `None` if it refers to a
+    /// channel that `channel` can't read.
+    fn eval(&self, value: f64, channel: &dyn Fn(&str) -> Option<f64>) -> Option<f64> {
+        Some(match self {
+            Self::Number(number) => *number,
+            Self::Value => value,
+            Self::Channel(name) => channel(name)?,
+            Self::Negate(inner) => -inner.eval(value, channel)?,
+            Self::Exp(inner) => inner.eval(value, channel)?.exp(),
+            Self::Ln(inner) => inner.eval(value, channel)?.ln(),
+            Self::Binary(left, op, right) => {
+                let (left, right) = (left.eval(value, channel)?, right.eval(value, channel)?);
+
+                match op {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    _ => left / right,
+                }
+            }
+        })
+    }
+}
+
+/// The bus a chip pattern matches: either a bus number, or for I2C chips the adapter a `bus`
+/// statement gave that number to.
+#[derive(Clone, Debug, PartialEq)]
+enum I2cBus {
+    Number(u32),
+    Adapter(String),
+}
+
+/// A chip name of a `chip` statement, in which any part may be `*`, e.g. `nct6798-isa-*`.
+#[derive(Clone, Debug, PartialEq)]
+struct ChipPattern {
+    prefix: Option<String>,
+    bus: Option<BusType>,
+    bus_number: Option<I2cBus>,
+    address: Option<u32>,
+}
+
+impl ChipPattern {
+    fn parse(pattern: &str) -> Option<Self> {
+        let mut parts = pattern.split('-');
+        let wildcard = |part: &str| (part != "*").then(|| part.to_string());
+        let hex = |part: Option<&str>| match part {
+            None | Some("*") => Some(None),
+            Some(part) => u32::from_str_radix(part, 16).ok().map(Some),
+        };
+
+        let mut chip = Self {
+            prefix: wildcard(parts.next()?),
+            bus: None,
+            bus_number: None,
+            address: None,
+        };
+
+        match parts.next() {
+            None | Some("*") => (),
+            Some(bus @ ("i2c" | "spi" | "hid" | "scsi")) => {
+                chip.bus = Some(match bus {
+                    "i2c" => BusType::I2c,
+                    "spi" => BusType::Spi,
+                    "hid" => BusType::Hid,
+                    _ => BusType::Scsi,
+                });
+                chip.bus_number = match parts.next() {
+                    None | Some("*") => None,
+                    Some(bus) => Some(I2cBus::Number(bus.parse().ok()?)),
+                };
+                chip.address = hex(parts.next())?;
+            }
+            Some(bus) => {
+                chip.bus = Some(match bus {
+                    "isa" => BusType::Isa,
+                    "pci" => BusType::Pci,
+                    "virtual" => BusType::Virtual,
+                    "acpi" => BusType::Acpi,
+                    "mdio" => BusType::Mdio,
+                    _ => return None,
+                });
+                chip.address = hex(parts.next())?;
+            }
+        }
+
+        parts.next().is_none().then_some(chip)
+    }
+
+    fn matches(&self, chip: &ChipName) -> bool {
+        let bus_number = match &self.bus_number {
+            None => true,
+            Some(I2cBus::Number(number)) => *number == chip.bus_number,
+            Some(I2cBus::Adapter(adapter)) => chip.adapter.as_ref() == Some(adapter),
+        };
+
+        self.prefix.iter().all(|prefix| *prefix == chip.prefix)
+            && self.bus.iter().all(|bus| *bus == chip.bus)
+            && bus_number
+            && self.address.iter().all(|address| *address == chip.address)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Statement {
+    Label(String, String),
+    Ignore(String),
+    Compute(String, Expression, Expression),
+    Set(String, Expression),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ChipBlock {
+    patterns: Vec<ChipPattern>,
+    statements: Vec<Statement>,
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> ConfigError {
+        let line = self
+            .tokens
+            .get(self.position.saturating_sub(1))
+            .map_or(1, |(line, _)| *line);
+
+        ConfigError {
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn name(&mut self, what: &str) -> Result<String, ConfigError> {
+        match self.next() {
+            Some(Token::Name(name)) => Ok(name),
+            _ => Err(self.error(format!("expected {}", what))),
+        }
+    }
+
+    fn string(&mut self, what: &str) -> Result<String, ConfigError> {
+        match self.next() {
+            Some(Token::String(string)) => Ok(string),
+            _ => Err(self.error(format!("expected {}", what))),
+        }
+    }
+
+    fn symbol(&mut self, symbol: char) -> Result<(), ConfigError> {
+        match self.next() {
+            Some(Token::Symbol(c)) if c == symbol => Ok(()),
+            _ => Err(self.error(format!("expected '{}'", symbol))),
+        }
+    }
+
+    fn end_of_statement(&mut self) -> Result<(), ConfigError> {
+        match self.next() {
+            Some(Token::Newline) => Ok(()),
+            _ => Err(self.error("expected the end of the line")),
+        }
+    }
+
+    /// `expression := term (('+' | '-') term)*`
+    fn expression(&mut self) -> Result<Expression, ConfigError> {
+        let mut expression = self.term()?;
+
+        while let Some(&Token::Symbol(op @ ('+' | '-'))) = self.peek() {
+            self.next();
+            expression = Expression::Binary(Box::new(expression), op, Box::new(self.term()?));
+        }
+
+        Ok(expression)
+    }
+
+    /// `term := unary (('*' | '/') unary)*`
+    fn term(&mut self) -> Result<Expression, ConfigError> {
+        let mut term = self.unary()?;
+
+        while let Some(&Token::Symbol(op @ ('*' | '/'))) = self.peek() {
+            self.next();
+            term = Expression::Binary(Box::new(term), op, Box::new(self.unary()?));
+        }
+
+        Ok(term)
+    }
+
+    /// `unary := ('-' | '^' | '`') unary | number | '@' | channel | '(' expression ')'`
+    fn unary(&mut self) -> Result<Expression, ConfigError> {
+        Ok(match self.next() {
+            Some(Token::Symbol('-')) => Expression::Negate(Box::new(self.unary()?)),
+            Some(Token::Symbol('^')) => Expression::Exp(Box::new(self.unary()?)),
+            Some(Token::Symbol('`')) => Expression::Ln(Box::new(self.unary()?)),
+            Some(Token::Symbol('@')) => Expression::Value,
+            Some(Token::Number(number)) => Expression::Number(number),
+            Some(Token::Name(name)) => Expression::Channel(name),
+            Some(Token::Symbol('(')) => {
+                let expression = self.expression()?;
+                self.symbol(')')?;
+                expression
+            }
+            _ => return Err(self.error("expected an expression")),
+        })
+    }
+}
+
+/// The statements of an lm-sensors configuration that apply to a single chip, with later
+/// statements taking precedence over earlier ones.
+#[derive(Clone, Debug, Default)]
+pub struct ChipConfig {
+    labels: HashMap<String, String>,
+    ignored: HashSet<String>,
+    computes: HashMap<String, (Expression, Expression)>,
+    sets: Vec<(String, Expression)>,
+}
+
+/// The factor that converts the sysfs values of the given channel to the units lm-sensors
+/// computes in, e.g. from millivolts to volts.
+fn channel_scale(channel: &str) -> f64 {
+    if channel.starts_with("power") || channel.starts_with("energy") {
+        1e-6
+    } else if channel.starts_with("fan") {
+        1.0
+    } else {
+        1e-3
+    }
+}
+
+/// Reads the raw value of a channel of the chip in the given directory, e.g. `in0`.
+fn read_channel(path: &Path, channel: &str) -> Option<f64> {
+    let value = std::fs::read_to_string(path.join(format!("{}_input", channel))).ok()?;
+    Some(value.trim().parse::<f64>().ok()? * channel_scale(channel))
+}
+
+impl ChipConfig {
+    /// The label given to the channel, e.g. `temp1`, if any.
+    #[must_use]
+    pub fn label(&self, channel: &str) -> Option<&str> {
+        self.labels.get(channel).map(String::as_str)
+    }
+
+    /// Whether the channel, e.g. `temp3`, is ignored.
+    #[must_use]
+    pub fn is_ignored(&self, channel: &str) -> bool {
+        self.ignored.contains(channel)
+    }
+
+    /// Converts a value read from the channel, in degrees Celsius, volts, amperes, watts, joules
+    /// or RPM, using its `compute` statement. Values of channels without one, or whose statement
+    /// refers to other channels, are returned as they are.
+    #[must_use]
+    pub fn compute(&self, channel: &str, value: f64) -> f64 {
+        self.convert(channel, value, true, &|_| None)
+    }
+
+    fn convert(
+        &self,
+        channel: &str,
+        value: f64,
+        forward: bool,
+        other: &dyn Fn(&str) -> Option<f64>,
+    ) -> f64 {
+        self.computes
+            .get(channel)
+            .and_then(|(from, to)| if forward { from } else { to }.eval(value, other))
+            .unwrap_or(value)
+    }
+
+    /// Converts a value read from a channel of the chip in the given directory, reading any other
+    /// channels its `compute` statement refers to from there.
+    pub(super) fn compute_in(&self, path: &Path, channel: &str, value: f64) -> f64 {
+        self.convert(channel, value, true, &|other| read_channel(path, other))
+    }
+
+    /// Writes the limits given by `set` statements to the chip in the given directory, like
+    /// `sensors -s` does. Values are converted back to sysfs units using the inverse expression
+    /// of the channel's `compute` statement.
+    pub(super) fn apply_limits(&self, path: &Path) -> Result<(), LinuxError> {
+        let other = |channel: &str| read_channel(path, channel);
+
+        for (attribute, expression) in &self.sets {
+            let channel = attribute.split('_').next().unwrap_or(attribute);
+            let value = expression.eval(0.0, &other).ok_or_else(|| {
+                LinuxError::InvalidData(format!("cannot compute the value of {}", attribute))
+            })?;
+            let value = self.convert(channel, value, false, &other) / channel_scale(channel);
+
+            std::fs::write(path.join(attribute), format!("{}", value.round() as i64))?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the configuration has any statements for the chip.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+            && self.ignored.is_empty()
+            && self.computes.is_empty()
+            && self.sets.is_empty()
+    }
+}
+
+/// An lm-sensors configuration, made of `bus`, `chip`, `label`, `ignore`, `compute` and `set`
+/// statements.
+#[derive(Clone, Debug, Default)]
+pub struct SensorsConfig {
+    chips: Vec<ChipBlock>,
+    errors: Vec<String>,
+}
+
+impl SensorsConfig {
+    /// Parses an lm-sensors configuration file. `bus` statements only apply to the chip
+    /// statements of the same file.
+    pub fn parse(source: &str) -> Result<Self, ConfigError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let mut buses = HashMap::new();
+        let mut chips = Vec::<ChipBlock>::new();
+
+        while let Some(token) = parser.next() {
+            let keyword = match token {
+                Token::Newline => continue,
+                Token::Name(keyword) => keyword,
+                _ => return Err(parser.error("expected a statement")),
+            };
+
+            let statement = match keyword.as_str() {
+                "bus" => {
+                    let bus = parser.string("a bus name")?;
+                    let adapter = parser.string("an adapter name")?;
+                    // Older configurations also name the algorithm, which is ignored.
+                    while let Some(Token::String(_)) = parser.peek() {
+                        parser.next();
+                    }
+
+                    let number = bus
+                        .strip_prefix("i2c-")
+                        .and_then(|number| number.parse::<u32>().ok())
+                        .ok_or_else(|| parser.error(format!("invalid bus name {}", bus)))?;
+                    buses.insert(number, adapter);
+                    parser.end_of_statement()?;
+                    continue;
+                }
+                "chip" => {
+                    let mut patterns = vec![parser.string("a chip name")?];
+                    while let Some(Token::String(_)) = parser.peek() {
+                        patterns.push(parser.string("a chip name")?);
+                    }
+
+                    let patterns = patterns
+                        .iter()
+                        .map(|pattern| {
+                            ChipPattern::parse(pattern).ok_or_else(|| {
+                                parser.error(format!("invalid chip name {}", pattern))
+                            })
+                        })
+                        .collect::<Result<_, _>>()?;
+
+                    chips.push(ChipBlock {
+                        patterns,
+                        statements: Vec::new(),
+                    });
+                    parser.end_of_statement()?;
+                    continue;
+                }
+                "label" => {
+                    Statement::Label(parser.name("a feature name")?, parser.string("a label")?)
+                }
+                "ignore" => Statement::Ignore(parser.name("a feature name")?),
+                "compute" => {
+                    let channel = parser.name("a feature name")?;
+                    let from = parser.expression()?;
+                    parser.symbol(',')?;
+                    Statement::Compute(channel, from, parser.expression()?)
+                }
+                "set" => Statement::Set(parser.name("an attribute name")?, parser.expression()?),
+                _ => return Err(parser.error(format!("unknown statement {}", keyword))),
+            };
+
+            chips
+                .last_mut()
+                .ok_or_else(|| parser.error(format!("{} statement outside of a chip", keyword)))?
+                .statements
+                .push(statement);
+            parser.end_of_statement()?;
+        }
+
+        // Bus numbers are specific to the machine the configuration was written for, so I2C
+        // chips are matched by the adapter a bus statement names rather than by number.
+        for pattern in chips.iter_mut().flat_map(|chip| &mut chip.patterns) {
+            if let (Some(BusType::I2c), Some(I2cBus::Number(number))) =
+                (pattern.bus, &pattern.bus_number)
+            {
+                if let Some(adapter) = buses.get(number) {
+                    pattern.bus_number = Some(I2cBus::Adapter(adapter.clone()));
+                }
+            }
+        }
+
+        Ok(Self {
+            chips,
+            errors: Vec::new(),
+        })
+    }
+
+    /// Reads the system's lm-sensors configuration: /etc/sensors3.conf, or /etc/sensors.conf if
+    /// it doesn't exist, followed by every file in /etc/sensors.d in alphabetical order. Later
+    /// files take precedence. A system without any configuration has an empty one.
+    ///
+    /// Files that can't be read or parsed are skipped rather than failing the whole configuration,
+    /// and are listed in [`errors`](Self::errors).
+    #[must_use]
+    pub fn load(root: &SystemRoot) -> Self {
+        let mut paths = ["/etc/sensors3.conf", "/etc/sensors.conf"]
+            .into_iter()
+            .map(|path| root.resolve(path))
+            .find(|path| path.is_file())
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        if let Ok(entries) = root.resolve("/etc/sensors.d").read_dir() {
+            let mut files = entries
+                .filter_map(Result::ok)
+                .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect::<Vec<_>>();
+            files.sort();
+            paths.extend(files);
+        }
+
+        let mut config = Self::default();
+        for path in paths {
+            let file = match std::fs::read_to_string(&path) {
+                Ok(source) => Self::parse(&source).map_err(|err| err.to_string()),
+                Err(err) => Err(err.to_string()),
+            };
+
+            match file {
+                Ok(file) => config.chips.extend(file.chips),
+                Err(err) => config.errors.push(format!("{}: {}", path.display(), err)),
+            }
+        }
+
+        config
+    }
+
+    /// The files [`load`](Self::load) skipped, each with why it couldn't be read or parsed, e.g.
+    /// `/etc/sensors.d/fans.conf: line 3: unknown statement lable`.
+    #[must_use]
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// Whether the configuration has no chip statements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.chips.is_empty()
+    }
+
+    /// The statements that apply to the given chip.
+    #[must_use]
+    pub fn chip(&self, chip: &ChipName) -> ChipConfig {
+        let mut config = ChipConfig::default();
+        let statements = self
+            .chips
+            .iter()
+            .filter(|block| block.patterns.iter().any(|pattern| pattern.matches(chip)))
+            .flat_map(|block| &block.statements);
+
+        for statement in statements {
+            match statement.clone() {
+                Statement::Label(channel, label) => {
+                    config.labels.insert(channel, label);
+                }
+                Statement::Ignore(channel) => {
+                    config.ignored.insert(channel);
+                }
+                Statement::Compute(channel, from, to) => {
+                    config.computes.insert(channel, (from, to));
+                }
+                Statement::Set(attribute, value) => config.sets.push((attribute, value)),
+            }
+        }
+
+        config
+    }
+}
@@ -0,0 +1,431 @@
+//! Management of the kernel's thermal framework through /sys/class/thermal: the cooling devices
+//! that throttle hardware, and the thermal zones whose governors drive them.
+//!
+//! This is what cools SoCs without PWM fans, e.g. by capping the CPU frequency once a passive
+//! trip point is crossed. Temperatures of thermal zones are read as components instead.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use super::{parse_temperature, LinuxError, LinuxError::InvalidData, SystemRoot};
+use crate::{ReadingStatus, Temperature};
+
+/// Whether the kernel monitors a thermal zone.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ThermalZoneMode {
+    /// The zone is polled and its governor acts on its trip points.
+    Enabled,
+    /// The zone is not monitored, so none of its cooling devices are driven by it.
+    Disabled,
+}
+
+impl ThermalZoneMode {
+    /// The value of the zone's `mode` attribute for this mode.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Enabled => "enabled",
+            Self::Disabled => "disabled",
+        }
+    }
+}
+
+/// What happens when a thermal zone crosses a trip point.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TripPointType {
+    /// Active cooling, such as a fan, is turned on.
+    Active,
+    /// Passive cooling, such as CPU frequency throttling, is applied.
+    Passive,
+    /// The system is notified that it is hot, usually without cooling anything.
+    Hot,
+    /// The system is shut down.
+    Critical,
+}
+
+impl FromStr for TripPointType {
+    type Err = LinuxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "active" => Ok(Self::Active),
+            "passive" => Ok(Self::Passive),
+            "hot" => Ok(Self::Hot),
+            "critical" => Ok(Self::Critical),
+            other => Err(InvalidData(format!(
+                "read invalid trip point type {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// A trip point of a thermal zone, i.e. `trip_point_N_*`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TripPoint {
+    /// The index of the trip point, i.e. `N` in `trip_point_N_temp`.
+    pub index: u32,
+    /// What happens when the trip point is crossed.
+    pub kind: TripPointType,
+    /// The temperature the trip point is crossed at.
+    pub temperature: Temperature,
+    /// How far below the trip point the temperature has to fall for it to no longer apply, if
+    /// the zone reports one.
+    pub hysteresis: Option<Temperature>,
+}
+
+/// A cooling device bound to a thermal zone, i.e. `cdevN` along with its attributes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoolingBinding {
+    /// The index of the bound cooling device, i.e. `N` in `cooling_deviceN`.
+    pub cooling_device: u32,
+    /// The index of the trip point the cooling device is bound to, or `None` if it is bound to
+    /// the zone as a whole.
+    pub trip_point: Option<u32>,
+    /// The weight of the cooling device relative to the zone's other cooling devices, used by
+    /// governors such as `power_allocator`, if the kernel supports it.
+    pub weight: Option<u32>,
+}
+
+/// Parses the index at the end of a sysfs entry such as `cooling_device3`.
+fn index(path: &Path, prefix: &str) -> Option<u32> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix(prefix)?
+        .parse()
+        .ok()
+}
+
+fn read_string(path: &Path, attribute: &str) -> Option<String> {
+    std::fs::read_to_string(path.join(attribute))
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+fn read<T: FromStr>(path: &Path, attribute: &str) -> Option<T> {
+    read_string(path, attribute)?.parse().ok()
+}
+
+/// The status of something whose refresh failed with the given error: stale if it was read
+/// before, unavailable otherwise.
+fn failed(status: &ReadingStatus, err: &LinuxError) -> ReadingStatus {
+    match status {
+        ReadingStatus::Unavailable(_) => ReadingStatus::Unavailable(err.to_string()),
+        _ => ReadingStatus::Stale(err.to_string()),
+    }
+}
+
+/// A cooling device from /sys/class/thermal/cooling_device*, such as a CPU frequency limit or an
+/// ACPI fan, whose cooling is set as a state from 0 (none) to its maximum state.
+pub struct CoolingDevice {
+    path: PathBuf,
+    index: u32,
+    kind: String,
+    state: u32,
+    max_state: u32,
+    status: ReadingStatus,
+}
+
+impl CoolingDevice {
+    /// The index of the cooling device, i.e. `N` in `cooling_deviceN`.
+    #[must_use]
+    pub const fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The type of the cooling device, e.g. `cpufreq-cpu0` or `Processor`.
+    #[must_use]
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// The current cooling state as of the last refresh.
+    #[must_use]
+    pub const fn state(&self) -> u32 {
+        self.state
+    }
+
+    /// The highest cooling state, i.e. the most cooling the device can provide.
+    #[must_use]
+    pub const fn max_state(&self) -> u32 {
+        self.max_state
+    }
+
+    /// Whether [`CoolingDevice::state`] is up to date. A device whose state can't be read keeps
+    /// its last known one.
+    #[must_use]
+    pub fn status(&self) -> &ReadingStatus {
+        &self.status
+    }
+
+    /// Forces the cooling state, from 0 to [`CoolingDevice::max_state`]. Unless every zone this
+    /// device is bound to uses the `user_space` governor, the kernel may change it again at any
+    /// time.
+    pub fn set_state(&mut self, state: u32) -> Result<(), LinuxError> {
+        if state > self.max_state {
+            return Err(InvalidData(format!(
+                "cooling state {} is above the maximum of {}",
+                state, self.max_state
+            )));
+        }
+
+        std::fs::write(self.path.join("cur_state"), state.to_string())?;
+        self.state = state;
+        self.status = ReadingStatus::Ok;
+        Ok(())
+    }
+
+    pub(super) fn refresh(&mut self) {
+        match self.read_state() {
+            Ok(state) => {
+                self.state = state;
+                self.status = ReadingStatus::Ok;
+            }
+            Err(err) => self.status = failed(&self.status, &err),
+        }
+        self.max_state = read(&self.path, "max_state").unwrap_or(self.max_state);
+    }
+
+    fn read_state(&self) -> Result<u32, LinuxError> {
+        let state = std::fs::read_to_string(self.path.join("cur_state"))?;
+        state
+            .trim()
+            .parse()
+            .map_err(|_| InvalidData(format!("read invalid cooling state {}", state.trim())))
+    }
+}
+
+/// The governor and cooling setup of a thermal zone from /sys/class/thermal/thermal_zone*. This
+/// includes zones that are read through hwmon instead.
+pub struct ThermalZone {
+    path: PathBuf,
+    index: u32,
+    kind: String,
+    mode: Option<ThermalZoneMode>,
+    policy: Option<String>,
+    available_policies: Vec<String>,
+    trip_points: Vec<TripPoint>,
+    bindings: Vec<CoolingBinding>,
+    status: ReadingStatus,
+}
+
+impl ThermalZone {
+    /// The index of the zone, i.e. `N` in `thermal_zoneN`.
+    #[must_use]
+    pub const fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The type of the zone, e.g. `x86_pkg_temp` or `cpu-thermal`.
+    #[must_use]
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Whether the zone is monitored, if the kernel reports it.
+    #[must_use]
+    pub const fn mode(&self) -> Option<ThermalZoneMode> {
+        self.mode
+    }
+
+    /// The governor that drives the zone's cooling devices, e.g. `step_wise`.
+    #[must_use]
+    pub fn policy(&self) -> Option<&str> {
+        self.policy.as_deref()
+    }
+
+    /// The governors the kernel was built with, any of which can be set as the zone's policy.
+    #[must_use]
+    pub fn available_policies(&self) -> &[String] {
+        &self.available_policies
+    }
+
+    /// The trip points of the zone, ordered by index. Disabled trip points are left out.
+    #[must_use]
+    pub fn trip_points(&self) -> &[TripPoint] {
+        &self.trip_points
+    }
+
+    /// The cooling devices bound to the zone.
+    #[must_use]
+    pub fn cooling_bindings(&self) -> &[CoolingBinding] {
+        &self.bindings
+    }
+
+    /// Whether the trip points and cooling bindings are up to date. A zone whose trip points
+    /// can't be read keeps its last known ones.
+    #[must_use]
+    pub fn status(&self) -> &ReadingStatus {
+        &self.status
+    }
+
+    /// Enables or disables monitoring of the zone. Disabling a zone stops its governor from
+    /// cooling the hardware, so it is up to the caller to do so instead.
+    pub fn set_mode(&mut self, mode: ThermalZoneMode) -> Result<(), LinuxError> {
+        std::fs::write(self.path.join("mode"), mode.as_str())?;
+        self.mode = Some(mode);
+        Ok(())
+    }
+
+    /// Switches the governor of the zone, e.g. to `user_space` so that cooling devices can be set
+    /// by hand, or to `power_allocator`. Governors that aren't one of
+    /// [`ThermalZone::available_policies`] are refused.
+    pub fn set_policy(&mut self, policy: &str) -> Result<(), LinuxError> {
+        if !self.available_policies.iter().any(|p| p == policy) {
+            return Err(InvalidData(format!(
+                "{} is not an available thermal governor for {}",
+                policy, self.kind
+            )));
+        }
+
+        std::fs::write(self.path.join("policy"), policy)?;
+        self.policy = Some(policy.to_string());
+        Ok(())
+    }
+
+    pub(super) fn refresh(&mut self) {
+        self.mode = match read_string(&self.path, "mode").as_deref() {
+            Some("enabled") => Some(ThermalZoneMode::Enabled),
+            Some("disabled") => Some(ThermalZoneMode::Disabled),
+            _ => None,
+        };
+        self.policy = read_string(&self.path, "policy");
+        self.available_policies = read_string(&self.path, "available_policies")
+            .map(|policies| policies.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        match self.read_trip_points() {
+            Ok((trip_points, bindings)) => {
+                self.trip_points = trip_points;
+                self.bindings = bindings;
+                self.status = ReadingStatus::Ok;
+            }
+            Err(err) => self.status = failed(&self.status, &err),
+        }
+    }
+
+    /// Reads the trip points and cooling bindings of the zone, each ordered by index.
+    fn read_trip_points(&self) -> Result<(Vec<TripPoint>, Vec<CoolingBinding>), LinuxError> {
+        let mut trip_points = Vec::new();
+        let mut bindings = Vec::new();
+
+        for entry in self.path.read_dir()?.filter_map(Result::ok) {
+            let path = entry.path();
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+
+            if let Some(index) = name
+                .strip_prefix("trip_point_")
+                .and_then(|name| name.strip_suffix("_temp"))
+                .and_then(|index| index.parse::<u32>().ok())
+            {
+                let trip = |attribute: &str| format!("trip_point_{}_{}", index, attribute);
+
+                // Disabled trip points report nonsensical temperatures, see
+                // get_sensors_from_thermal_zone.
+                let temperature = std::fs::read_to_string(&path)?;
+                let Some(temperature) = parse_temperature(&temperature)
+                    .filter(|temperature| *temperature >= Temperature::ABSOLUTE_ZERO)
+                else {
+                    continue;
+                };
+
+                trip_points.push(TripPoint {
+                    index,
+                    kind: std::fs::read_to_string(self.path.join(trip("type")))?.parse()?,
+                    temperature,
+                    hysteresis: read_string(&self.path, &trip("hyst"))
+                        .as_deref()
+                        .and_then(parse_temperature),
+                });
+            } else if let Some(binding) = index(&path, "cdev") {
+                let Some(cooling_device) = std::fs::read_link(&path)
+                    .ok()
+                    .and_then(|device| index(&device, "cooling_device"))
+                else {
+                    continue;
+                };
+
+                // Devices bound to the zone as a whole have a trip point of -1.
+                let trip_point = read::<i64>(&self.path, &format!("cdev{}_trip_point", binding))
+                    .and_then(|trip| u32::try_from(trip).ok());
+
+                bindings.push(CoolingBinding {
+                    cooling_device,
+                    trip_point,
+                    weight: read(&self.path, &format!("cdev{}_weight", binding)),
+                });
+            }
+        }
+
+        trip_points.sort_by_key(|trip| trip.index);
+        bindings.sort_by_key(|binding| binding.cooling_device);
+
+        Ok((trip_points, bindings))
+    }
+}
+
+/// Lists the entries of /sys/class/thermal that start with the given prefix, along with their
+/// index, ordered by index.
+fn entries(root: &SystemRoot, prefix: &str) -> Result<Vec<(PathBuf, u32)>, LinuxError> {
+    let path = root.resolve("/sys/class/thermal");
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = path
+        .read_dir()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| Some((entry.path(), index(&entry.path(), prefix)?)))
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|(_, index)| *index);
+
+    Ok(entries)
+}
+
+/// Returns every cooling device in /sys/class/thermal, including those whose state can't be read.
+pub(super) fn get_cooling_devices(root: &SystemRoot) -> Result<Vec<CoolingDevice>, LinuxError> {
+    let mut devices = Vec::new();
+
+    for (path, index) in entries(root, "cooling_device")? {
+        let mut device = CoolingDevice {
+            kind: read_string(&path, "type").unwrap_or_default(),
+            max_state: read(&path, "max_state").unwrap_or_default(),
+            state: 0,
+            status: ReadingStatus::Unavailable(String::new()),
+            path,
+            index,
+        };
+        device.refresh();
+        devices.push(device);
+    }
+
+    Ok(devices)
+}
+
+/// Returns every thermal zone in /sys/class/thermal, including those whose trip points can't be
+/// read.
+pub(super) fn get_thermal_zones(root: &SystemRoot) -> Result<Vec<ThermalZone>, LinuxError> {
+    let mut zones = Vec::new();
+
+    for (path, index) in entries(root, "thermal_zone")? {
+        let mut zone = ThermalZone {
+            kind: read_string(&path, "type").unwrap_or_default(),
+            mode: None,
+            policy: None,
+            available_policies: Vec::new(),
+            trip_points: Vec::new(),
+            bindings: Vec::new(),
+            status: ReadingStatus::Unavailable(String::new()),
+            path,
+            index,
+        };
+        zone.refresh();
+        zones.push(zone);
+    }
+
+    Ok(zones)
+}
@@ -0,0 +1,236 @@
+//! Maps CPU temperature channels, such as coretemp's "Core 0" or k10temp's "Tccd1", to the
+//! packages, dies and logical CPUs they measure using /sys/devices/system/cpu/cpu*/topology.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+use super::SystemRoot;
+use crate::CpuLocation;
+
+/// A single logical CPU, as listed in /sys/devices/system/cpu.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LogicalCpu {
+    /// The ID of this logical CPU, e.g. 3 for `cpu3`.
+    pub id: u32,
+    /// The physical package (socket) this CPU belongs to.
+    pub package: u32,
+    /// The die within the package this CPU belongs to.
+    pub die: u32,
+    /// The core within the package this CPU belongs to. Hyperthreads share their core ID.
+    pub core: u32,
+    /// The ID of the L3 cache this CPU uses, if it has one.
+    pub l3: Option<u32>,
+}
+
+/// The topology of every logical CPU on the system.
+#[derive(Clone, Debug, Default)]
+pub struct CpuTopology {
+    cpus: Vec<LogicalCpu>,
+}
+
+impl CpuTopology {
+    /// Reads the CPU topology from sysfs. CPUs without topology information, e.g. offline CPUs,
+    /// are left out.
+    #[must_use]
+    pub fn read(root: &SystemRoot) -> Self {
+        let path = root.resolve("/sys/devices/system/cpu");
+        let read = |path: &Path| -> Option<u32> {
+            std::fs::read_to_string(path).ok()?.trim().parse().ok()
+        };
+
+        let mut cpus = path
+            .read_dir()
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let id = entry
+                    .file_name()
+                    .to_str()?
+                    .strip_prefix("cpu")?
+                    .parse()
+                    .ok()?;
+                let path = entry.path();
+
+                Some(LogicalCpu {
+                    id,
+                    package: read(&path.join("topology/physical_package_id"))?,
+                    die: read(&path.join("topology/die_id")).unwrap_or(0),
+                    core: read(&path.join("topology/core_id"))?,
+                    l3: read(&path.join("cache/index3/id")),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        cpus.sort_by_key(|cpu| cpu.id);
+        Self { cpus }
+    }
+
+    /// Every known logical CPU, ordered by ID.
+    #[must_use]
+    pub fn cpus(&self) -> &[LogicalCpu] {
+        &self.cpus
+    }
+
+    /// The IDs of every physical package, in ascending order.
+    #[must_use]
+    pub fn packages(&self) -> Vec<u32> {
+        self.cpus
+            .iter()
+            .map(|cpu| cpu.package)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    fn cpus_where(&self, f: impl Fn(&LogicalCpu) -> bool) -> Vec<u32> {
+        self.cpus
+            .iter()
+            .filter(|cpu| f(cpu))
+            .map(|cpu| cpu.id)
+            .collect()
+    }
+
+    /// The logical CPUs of each of the `count` CCDs in the given package, inferred from the L3
+    /// caches they share. On processors with several core complexes per CCD, such as Zen 2, each
+    /// CCD spans several L3 caches.
+    fn ccds(&self, package: u32, count: usize) -> Vec<Vec<u32>> {
+        let caches = self
+            .cpus
+            .iter()
+            .filter(|cpu| cpu.package == package)
+            .filter_map(|cpu| cpu.l3)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        if count == 0 || caches.len() < count || caches.len() % count != 0 {
+            return vec![Vec::new(); count];
+        }
+
+        caches
+            .chunks(caches.len() / count)
+            .map(|chunk| {
+                self.cpus_where(|cpu| {
+                    cpu.package == package && cpu.l3.is_some_and(|l3| chunk.contains(&l3))
+                })
+            })
+            .collect()
+    }
+
+    fn package_location(&self, package: u32) -> CpuLocation {
+        CpuLocation {
+            package,
+            die: None,
+            core: None,
+            cpus: self.cpus_where(|cpu| cpu.package == package),
+        }
+    }
+
+    /// Maps the temperature channels of the given hwmon chip to CPU locations, keyed by channel,
+    /// e.g. `temp2`. Chips that aren't CPU temperature drivers have no locations.
+    pub(super) fn locate_channels(
+        &self,
+        chip: Option<&str>,
+        path: &Path,
+        device_path: &Path,
+    ) -> HashMap<String, CpuLocation> {
+        let labels = temperature_labels(path);
+        let device = device_path
+            .canonicalize()
+            .ok()
+            .and_then(|path| Some(path.file_name()?.to_str()?.to_string()))
+            .unwrap_or_default();
+
+        match chip {
+            Some("coretemp") => {
+                // Each package has its own coretemp device, e.g. coretemp.1 for the second.
+                let package = labels
+                    .values()
+                    .find_map(|label| label.strip_prefix("Package id ")?.parse().ok())
+                    .or_else(|| device.rsplit_once('.')?.1.parse().ok())
+                    .unwrap_or(0);
+
+                labels
+                    .into_iter()
+                    .map(|(channel, label)| {
+                        let location = match label
+                            .strip_prefix("Core ")
+                            .and_then(|core| core.parse().ok())
+                        {
+                            Some(core) => CpuLocation {
+                                package,
+                                die: None,
+                                core: Some(core),
+                                cpus: self
+                                    .cpus_where(|cpu| cpu.package == package && cpu.core == core),
+                            },
+                            None => self.package_location(package),
+                        };
+
+                        (channel, location)
+                    })
+                    .collect()
+            }
+            Some("k10temp" | "zenpower") => {
+                // Each package has its own northbridge PCI function, starting at device 18h.
+                let package = device
+                    .rsplit(':')
+                    .next()
+                    .and_then(|slot| u32::from_str_radix(slot.split('.').next()?, 16).ok())
+                    .and_then(|slot| slot.checked_sub(0x18))
+                    .unwrap_or(0);
+
+                let ccd = |label: &str| {
+                    label
+                        .strip_prefix("Tccd")?
+                        .parse::<u32>()
+                        .ok()?
+                        .checked_sub(1)
+                };
+                let count = labels
+                    .values()
+                    .filter_map(|label| ccd(label))
+                    .max()
+                    .map_or(0, |max| max as usize + 1);
+                let ccds = self.ccds(package, count);
+
+                labels
+                    .into_iter()
+                    .map(|(channel, label)| {
+                        let location = match ccd(&label) {
+                            Some(die) => CpuLocation {
+                                package,
+                                die: Some(die),
+                                core: None,
+                                cpus: ccds[die as usize].clone(),
+                            },
+                            None => self.package_location(package),
+                        };
+
+                        (channel, location)
+                    })
+                    .collect()
+            }
+            _ => HashMap::new(),
+        }
+    }
+}
+
+/// The labels of every labelled temperature channel in the given hwmon directory, keyed by channel.
+fn temperature_labels(path: &Path) -> HashMap<String, String> {
+    path.read_dir()
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let channel = name.to_str()?.strip_suffix("_label")?;
+
+            channel.starts_with("temp").then(|| {
+                let label = std::fs::read_to_string(entry.path()).ok()?;
+                Some((channel.to_string(), label.trim().to_string()))
+            })?
+        })
+        .collect()
+}
@@ -0,0 +1,89 @@
+//! Kernel uevent notifications, used to rescan as soon as sensors are hot-plugged rather than
+//! waiting for the next periodic rescan.
+
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+/// The subsystems whose devices are enumerated as components.
+const SUBSYSTEMS: [&str; 3] = ["hwmon", "thermal", "power_supply"];
+
+/// A non-blocking netlink socket subscribed to kernel uevents.
+pub(super) struct UeventMonitor {
+    socket: OwnedFd,
+}
+
+impl UeventMonitor {
+    /// Subscribes to kernel uevents. Returns `None` if the socket cannot be opened, e.g. inside
+    /// of a sandbox without netlink access, in which case only periodic rescans happen.
+    pub(super) fn new() -> Option<Self> {
+        // SAFETY: the socket is owned by the returned OwnedFd, and the address outlives the call
+        // to bind.
+        unsafe {
+            let fd = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                libc::NETLINK_KOBJECT_UEVENT,
+            );
+            if fd < 0 {
+                return None;
+            }
+            let socket = OwnedFd::from_raw_fd(fd);
+
+            let mut address = std::mem::zeroed::<libc::sockaddr_nl>();
+            address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            // The kernel's own broadcast group, as opposed to udev's re-broadcasts.
+            address.nl_groups = 1;
+
+            let bound = libc::bind(
+                socket.as_raw_fd(),
+                std::ptr::addr_of!(address).cast(),
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            );
+
+            (bound == 0).then_some(Self { socket })
+        }
+    }
+
+    /// Drains every pending uevent, returning whether any of them added or removed a device that
+    /// may be a component.
+    pub(super) fn poll(&self) -> bool {
+        let mut buffer = [0_u8; 8192];
+        let mut changed = false;
+
+        loop {
+            // SAFETY: the buffer is valid for writes of its whole length.
+            let len = unsafe {
+                libc::recv(
+                    self.socket.as_raw_fd(),
+                    buffer.as_mut_ptr().cast(),
+                    buffer.len(),
+                    libc::MSG_DONTWAIT,
+                )
+            };
+            if len <= 0 {
+                return changed;
+            }
+
+            changed |= is_hotplug(&buffer[..len as usize]);
+        }
+    }
+}
+
+/// Whether the given uevent, a list of NUL-separated `KEY=value` pairs following an
+/// `action@devpath` header, adds or removes a device of one of [`SUBSYSTEMS`].
+fn is_hotplug(event: &[u8]) -> bool {
+    let mut action = None;
+    let mut subsystem = None;
+
+    for field in event
+        .split(|&b| b == 0)
+        .filter_map(|f| std::str::from_utf8(f).ok())
+    {
+        match field.split_once('=') {
+            Some(("ACTION", value)) => action = Some(value),
+            Some(("SUBSYSTEM", value)) => subsystem = Some(value),
+            _ => (),
+        }
+    }
+
+    matches!(action, Some("add" | "remove")) && subsystem.is_some_and(|s| SUBSYSTEMS.contains(&s))
+}
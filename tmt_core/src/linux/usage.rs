@@ -0,0 +1,128 @@
+//! CPU utilisation computed from the deltas of /proc/stat between refreshes.
+
+use std::collections::HashMap;
+
+use super::{LinuxError, LinuxError::InvalidData, SystemRoot};
+
+/// The cumulative busy and total time of a CPU, in clock ticks.
+#[derive(Copy, Clone, Debug, Default)]
+struct Times {
+    busy: u64,
+    total: u64,
+}
+
+impl Times {
+    /// Parses the fields following the `cpu` or `cpuN` label of a /proc/stat line.
+    fn parse(fields: &str) -> Option<Self> {
+        let fields = fields
+            .split_whitespace()
+            .map(str::parse::<u64>)
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+
+        // user, nice, system, idle, iowait, irq, softirq and steal. The guest fields that follow
+        // are already accounted for in user and nice.
+        let total = fields.iter().take(8).sum();
+        let idle = fields.get(3)? + fields.get(4).unwrap_or(&0);
+
+        Some(Self {
+            busy: total - idle,
+            total,
+        })
+    }
+
+    /// The utilisation between an earlier sample and this one, from 0.0 to 100.0, or `None` if
+    /// no time has passed.
+    fn utilisation_since(self, earlier: Self) -> Option<f32> {
+        let total = self.total.checked_sub(earlier.total)?;
+        let busy = self.busy.checked_sub(earlier.busy)?;
+
+        (total > 0).then(|| (busy as f64 / total as f64 * 100.0) as f32)
+    }
+}
+
+/// Tracks the utilisation of every logical CPU, along with the system as a whole.
+#[derive(Clone, Debug, Default)]
+pub struct CpuUsage {
+    samples: HashMap<Option<u32>, Times>,
+    utilisation: HashMap<Option<u32>, f32>,
+}
+
+impl CpuUsage {
+    /// Takes an initial sample, so that utilisation is known from the first refresh onwards.
+    pub(super) fn new(root: &SystemRoot) -> Self {
+        Self {
+            samples: read_stat(root).unwrap_or_default(),
+            utilisation: HashMap::new(),
+        }
+    }
+
+    /// Samples /proc/stat again and recomputes utilisation since the previous sample.
+    pub(super) fn refresh(&mut self, root: &SystemRoot) -> Result<(), LinuxError> {
+        let samples = read_stat(root)?;
+
+        self.utilisation = samples
+            .iter()
+            .filter_map(|(cpu, times)| {
+                let utilisation = times.utilisation_since(*self.samples.get(cpu)?)?;
+                Some((*cpu, utilisation))
+            })
+            .collect();
+        self.samples = samples;
+
+        Ok(())
+    }
+
+    /// The utilisation of the whole system, from 0.0 to 100.0.
+    #[must_use]
+    pub fn total(&self) -> Option<f32> {
+        self.utilisation.get(&None).copied()
+    }
+
+    /// The utilisation of the given logical CPU, from 0.0 to 100.0.
+    #[must_use]
+    pub fn cpu(&self, id: u32) -> Option<f32> {
+        self.utilisation.get(&Some(id)).copied()
+    }
+
+    /// The average utilisation of the given logical CPUs, from 0.0 to 100.0. CPUs with unknown
+    /// utilisation, e.g. because they went offline, are left out.
+    #[must_use]
+    pub fn average(&self, cpus: &[u32]) -> Option<f32> {
+        let known = cpus
+            .iter()
+            .filter_map(|&id| self.cpu(id))
+            .collect::<Vec<_>>();
+
+        (!known.is_empty()).then(|| known.iter().sum::<f32>() / known.len() as f32)
+    }
+}
+
+/// Reads the times of every CPU from /proc/stat, keyed by logical CPU ID, or by `None` for the
+/// aggregate `cpu` line.
+fn read_stat(root: &SystemRoot) -> Result<HashMap<Option<u32>, Times>, LinuxError> {
+    let path = root.resolve("/proc/stat");
+
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let stat = std::fs::read_to_string(path)?;
+
+    stat.lines()
+        .filter_map(|line| {
+            let (label, fields) = line.split_once(char::is_whitespace)?;
+            let cpu = match label.strip_prefix("cpu")? {
+                "" => None,
+                id => Some(id.parse().ok()?),
+            };
+
+            Some((cpu, fields))
+        })
+        .map(|(cpu, fields)| {
+            Times::parse(fields)
+                .map(|times| (cpu, times))
+                .ok_or_else(|| InvalidData(format!("read invalid cpu times {:?}", fields)))
+        })
+        .collect()
+}
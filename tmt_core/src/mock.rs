@@ -0,0 +1,721 @@
+//! A provider of synthetic components whose readings follow scripted signals, for developing on
+//! top of `tmt_core` on machines without sensors, such as CI virtual machines.
+//!
+//! Components are described by a [`Scenario`], usually loaded from a TOML file:
+//!
+//! ```toml
+//! [[components]]
+//! label = "CPU"
+//! type = "Cpu"
+//! usage = { kind = "sine", mean = 50, amplitude = 40, period = 30 }
+//!
+//! [[components.temperatures]]
+//! label = "Package"
+//! signal = { kind = "ramp", from = 45, to = 105, duration = 60 }
+//! high = 85
+//! critical = 100
+//!
+//! [[components]]
+//! label = "CPU Fan"
+//! type = "Fan"
+//! rpm = { kind = "step", from = 900, to = 2400, at = 30 }
+//! max_rpm = 2600
+//! ```
+
+use std::f64::consts::TAU;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::{
+    BatteryState, BatteryStatus, Component, ComponentSnapshot, ComponentType, Error, ErrorKind,
+    Interface, ReadingStatus, SensorId, SensorKind, SensorReading, SystemInfo, Temperature,
+    TemperatureAlarms, TemperatureSnapshot,
+};
+
+/// The scenario used by [`MockComponents::default`]: a CPU, a GPU and a fan that vary slowly.
+const DEMO: &str = include_str!("../scenarios/demo.toml");
+
+/// A value that changes over time, in the unit of whatever it drives. Times are in seconds since
+/// the provider was created.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Signal {
+    /// Always the same value.
+    Constant {
+        /// The value.
+        value: f64,
+    },
+    /// Moves linearly from one value to another, then holds it, or starts over if `repeat` is
+    /// set.
+    Ramp {
+        /// The value at the start.
+        from: f64,
+        /// The value at the end.
+        to: f64,
+        /// How long it takes to get from one to the other.
+        duration: f64,
+        /// Whether to start over once `to` is reached.
+        #[serde(default)]
+        repeat: bool,
+    },
+    /// Oscillates around a value.
+    Sine {
+        /// The value oscillated around.
+        mean: f64,
+        /// The largest distance from the mean.
+        amplitude: f64,
+        /// How long one oscillation takes.
+        period: f64,
+    },
+    /// Jumps from one value to another at a given time.
+    Step {
+        /// The value before the step.
+        from: f64,
+        /// The value after the step.
+        to: f64,
+        /// When the step happens.
+        at: f64,
+    },
+    /// Varies randomly around a value. The same seed produces the same values at the same times.
+    Noise {
+        /// The value varied around.
+        mean: f64,
+        /// The largest distance from the mean.
+        amplitude: f64,
+        /// The seed of the random values.
+        #[serde(default)]
+        seed: u64,
+    },
+    /// Plays back values read from a file, one per line, starting over after the last one. Empty
+    /// lines and lines starting with `#` are skipped. Relative paths are resolved against the
+    /// directory of the scenario.
+    File {
+        /// The file to read values from.
+        path: PathBuf,
+        /// How long each value is held for.
+        #[serde(default = "default_interval")]
+        interval: f64,
+        /// The values read from the file.
+        #[serde(skip)]
+        values: Vec<f64>,
+    },
+}
+
+const fn default_interval() -> f64 {
+    1.0
+}
+
+/// Mixes the bits of the given value, to derive pseudo-random values from seeds and times.
+const fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Signal {
+    /// The value of this signal at the given time.
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn value_at(&self, elapsed: Duration) -> f64 {
+        let t = elapsed.as_secs_f64();
+
+        match self {
+            Self::Constant { value } => *value,
+            Self::Ramp {
+                from,
+                to,
+                duration,
+                repeat,
+            } => {
+                let progress = if *duration <= 0.0 {
+                    1.0
+                } else if *repeat {
+                    (t / duration).fract()
+                } else {
+                    (t / duration).min(1.0)
+                };
+                from + (to - from) * progress
+            }
+            Self::Sine {
+                mean,
+                amplitude,
+                period,
+            } => mean + amplitude * (TAU * t / period).sin(),
+            Self::Step { from, to, at } => {
+                if t < *at {
+                    *from
+                } else {
+                    *to
+                }
+            }
+            Self::Noise {
+                mean,
+                amplitude,
+                seed,
+            } => {
+                let bits = splitmix64(seed ^ splitmix64(elapsed.as_millis() as u64));
+                let unit = (bits >> 11) as f64 / (1_u64 << 53) as f64;
+                mean + amplitude * (unit * 2.0 - 1.0)
+            }
+            Self::File {
+                interval, values, ..
+            } => {
+                if values.is_empty() {
+                    return 0.0;
+                }
+                let index = if *interval > 0.0 {
+                    (t / interval) as usize
+                } else {
+                    0
+                };
+                values[index % values.len()]
+            }
+        }
+    }
+
+    /// Reads the values of file signals, resolving relative paths against the given directory.
+    /// Other signals are left as they are.
+    fn load(&mut self, base: &Path) -> Result<(), Error> {
+        let Self::File { path, values, .. } = self else {
+            return Ok(());
+        };
+
+        let path = base.join(&*path);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|err| Error::new(err.kind().into(), format!("{}: {}", path.display(), err)))?;
+        let invalid = |message: String| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), message),
+            )
+        };
+
+        *values = content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                line.parse()
+                    .map_err(|_| invalid(format!("line {}: invalid value {:?}", number, line)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        if values.is_empty() {
+            return Err(invalid("no values".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// A temperature reading of a mock component.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TemperatureSpec {
+    /// The label of the reading, which its ID is derived from, so it can't contain `#`.
+    pub label: String,
+    /// The temperature, in degrees Celsius.
+    pub signal: Signal,
+    /// The temperature considered high. Defaults to 80 °C.
+    #[serde(default = "default_high")]
+    pub high: Temperature,
+    /// The temperature considered critical. Defaults to 100 °C.
+    #[serde(default = "default_critical")]
+    pub critical: Temperature,
+    /// The utilisation of what the temperature is measured on, from 0 to 100, if any.
+    pub usage: Option<Signal>,
+}
+
+fn default_high() -> Temperature {
+    Temperature::from_celsius(80.0)
+}
+
+fn default_critical() -> Temperature {
+    Temperature::from_celsius(100.0)
+}
+
+/// A non-thermal sensor reading of a mock component.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SensorSpec {
+    /// The label of the reading, which its ID is derived from, so it can't contain `#`.
+    pub label: String,
+    /// What the reading measures.
+    pub kind: SensorKind,
+    /// The value, in the unit given by [`SensorKind::unit`].
+    pub signal: Signal,
+    /// The lowest value considered normal, if any.
+    pub min: Option<f64>,
+    /// The highest value considered normal, if any.
+    pub max: Option<f64>,
+}
+
+/// The charge state of a mock battery.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BatterySpec {
+    /// The charge, from 0 to 100.
+    pub charge: Signal,
+    /// Whether the battery is charging or discharging. Defaults to discharging.
+    #[serde(default = "default_battery_status")]
+    pub status: BatteryStatus,
+    /// The energy stored in the battery when it is full, in watt-hours, if known.
+    pub energy_full: Option<f64>,
+    /// The rate the battery is being charged or discharged at, in watts, if known.
+    pub power: Option<Signal>,
+}
+
+const fn default_battery_status() -> BatteryStatus {
+    BatteryStatus::Discharging
+}
+
+/// A mock component and the signals its readings follow.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ComponentSpec {
+    /// The label of the component, which its ID is derived from, so it can't contain `#`.
+    pub label: String,
+    /// The type of the component.
+    #[serde(rename = "type")]
+    pub component_type: ComponentType,
+    /// The temperature readings of the component. Components with any are thermal components.
+    #[serde(default)]
+    pub temperatures: Vec<TemperatureSpec>,
+    /// The non-thermal sensor readings of the component.
+    #[serde(default)]
+    pub sensors: Vec<SensorSpec>,
+    /// The utilisation of the component, from 0 to 100, if any.
+    pub usage: Option<Signal>,
+    /// The fan speed of the component in RPM. Components with one are fan components.
+    pub rpm: Option<Signal>,
+    /// The highest fan speed of the component in RPM, which its percentage is relative to if it
+    /// has no `usage`.
+    pub max_rpm: Option<f64>,
+    /// The charge state of the component. Components with one are battery components.
+    pub battery: Option<BatterySpec>,
+}
+
+impl ComponentSpec {
+    fn signals_mut(&mut self) -> impl Iterator<Item = &mut Signal> {
+        let battery = self
+            .battery
+            .iter_mut()
+            .flat_map(|battery| std::iter::once(&mut battery.charge).chain(battery.power.as_mut()));
+
+        self.temperatures
+            .iter_mut()
+            .flat_map(|reading| std::iter::once(&mut reading.signal).chain(reading.usage.as_mut()))
+            .chain(self.sensors.iter_mut().map(|reading| &mut reading.signal))
+            .chain(self.usage.as_mut())
+            .chain(self.rpm.as_mut())
+            .chain(battery)
+    }
+}
+
+/// The system information and components of a [`MockComponents`] provider.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// The system information reported by the provider.
+    #[serde(default = "default_system")]
+    pub system: SystemInfo,
+    /// The components of the provider.
+    #[serde(default)]
+    pub components: Vec<ComponentSpec>,
+}
+
+fn default_system() -> SystemInfo {
+    SystemInfo {
+        os_name: "Mock OS".to_string(),
+        cpu_name: "Mock CPU".to_string(),
+        device_model_name: "Mock Device".to_string(),
+        ..SystemInfo::default()
+    }
+}
+
+impl Scenario {
+    /// Parses a scenario from TOML, reading the values of file signals relative to the given
+    /// directory.
+    pub fn parse(scenario: &str, base: &Path) -> Result<Self, Error> {
+        let mut scenario: Self =
+            toml::from_str(scenario).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+
+        // IDs are derived from labels, with `#` separating a component from its channel.
+        for component in &scenario.components {
+            let labels = component
+                .temperatures
+                .iter()
+                .map(|reading| &reading.label)
+                .chain(component.sensors.iter().map(|reading| &reading.label));
+
+            if let Some(label) = std::iter::once(&component.label)
+                .chain(labels)
+                .find(|label| label.contains('#'))
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("label {:?} contains '#'", label),
+                ));
+            }
+        }
+
+        for signal in scenario
+            .components
+            .iter_mut()
+            .flat_map(ComponentSpec::signals_mut)
+        {
+            signal.load(base)?;
+        }
+
+        Ok(scenario)
+    }
+
+    /// Loads a scenario from a TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let scenario = std::fs::read_to_string(path)
+            .map_err(|err| Error::new(err.kind().into(), format!("{}: {}", path.display(), err)))?;
+
+        Self::parse(&scenario, path.parent().unwrap_or_else(|| Path::new("")))
+            .map_err(|err| Error::new(err.kind(), format!("{}: {}", path.display(), err)))
+    }
+}
+
+/// The built-in demo scenario.
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::parse(DEMO, Path::new("")).expect("the demo scenario is invalid")
+    }
+}
+
+/// A component of a [`MockComponents`] provider. Its readings are those of its last refresh.
+#[derive(Clone, Debug)]
+pub struct MockComponent {
+    spec: ComponentSpec,
+    current: ComponentSnapshot,
+}
+
+impl MockComponent {
+    fn new(spec: ComponentSpec) -> Self {
+        let id = SensorId::new(format!("mock:{}", spec.label));
+        let temperatures = spec
+            .temperatures
+            .iter()
+            .map(|reading| TemperatureSnapshot {
+                id: id.channel(&reading.label),
+                label: reading.label.clone(),
+                temperature: Temperature::default(),
+                max: Temperature::ABSOLUTE_ZERO,
+                high: reading.high,
+                critical: reading.critical,
+                min: None,
+                lowest: None,
+                low_critical: None,
+                emergency: None,
+                high_hysteresis: None,
+                critical_hysteresis: None,
+                alarms: TemperatureAlarms::empty(),
+                status: ReadingStatus::Ok,
+                usage: None,
+                cpu_location: None,
+            })
+            .collect();
+
+        let current = ComponentSnapshot {
+            label: spec.label.clone(),
+            component_type: spec.component_type,
+            temperatures,
+            sensors: Vec::new(),
+            battery: None,
+            percentage: None,
+            rpm: None,
+            id,
+        };
+
+        let mut component = Self { spec, current };
+        component.sample(Duration::ZERO);
+        component
+    }
+
+    /// The signals this component's readings follow.
+    #[must_use]
+    pub const fn spec(&self) -> &ComponentSpec {
+        &self.spec
+    }
+
+    /// Updates every reading to the value of its signal at the given time.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn sample(&mut self, elapsed: Duration) {
+        let spec = &self.spec;
+        let current = &mut self.current;
+
+        for (reading, source) in current.temperatures.iter_mut().zip(&spec.temperatures) {
+            let temperature = Temperature::from_celsius(source.signal.value_at(elapsed));
+
+            reading.temperature = temperature;
+            reading.max = reading.max.max(temperature);
+            reading.lowest = Some(reading.lowest.map_or(temperature, |t| t.min(temperature)));
+            reading.usage = source
+                .usage
+                .as_ref()
+                .map(|usage| usage.value_at(elapsed).clamp(0.0, 100.0) as f32);
+
+            reading.alarms = TemperatureAlarms::empty();
+            if temperature >= reading.high {
+                reading.alarms |= TemperatureAlarms::MAX;
+            }
+            if temperature >= reading.critical {
+                reading.alarms |= TemperatureAlarms::CRITICAL;
+            }
+        }
+
+        current.sensors = spec
+            .sensors
+            .iter()
+            .map(|reading| SensorReading {
+                id: current.id.channel(&reading.label),
+                label: reading.label.clone(),
+                kind: reading.kind,
+                value: reading.signal.value_at(elapsed),
+                min: reading.min,
+                max: reading.max,
+                status: ReadingStatus::Ok,
+            })
+            .collect();
+
+        current.rpm = spec.rpm.as_ref().map(|rpm| rpm.value_at(elapsed).max(0.0));
+
+        let charge = spec
+            .battery
+            .as_ref()
+            .map(|battery| battery.charge.value_at(elapsed).clamp(0.0, 100.0));
+        current.battery = spec.battery.as_ref().map(|battery| {
+            let charge = charge.unwrap_or_default();
+            let energy = battery.energy_full.map(|full| full * charge / 100.0);
+            let power = battery.power.as_ref().map(|power| power.value_at(elapsed));
+            let hours = |energy: f64| {
+                power
+                    .filter(|power| *power > 0.0 && energy > 0.0)
+                    .and_then(|power| Duration::try_from_secs_f64(energy / power * 3600.0).ok())
+            };
+
+            BatteryState {
+                status: battery.status,
+                energy,
+                energy_full: battery.energy_full,
+                power,
+                time_to_empty: match (battery.status, energy) {
+                    (BatteryStatus::Discharging, Some(energy)) => hours(energy),
+                    _ => None,
+                },
+                time_to_full: match (battery.status, energy, battery.energy_full) {
+                    (BatteryStatus::Charging, Some(energy), Some(full)) => hours(full - energy),
+                    _ => None,
+                },
+            }
+        });
+
+        let usage = spec.usage.as_ref().map(|usage| usage.value_at(elapsed));
+        let fan = current
+            .rpm
+            .zip(spec.max_rpm)
+            .filter(|(_, max)| *max > 0.0)
+            .map(|(rpm, max)| rpm / max * 100.0);
+        current.percentage = usage
+            .or(fan)
+            .or(charge)
+            .map(|percentage| percentage.clamp(0.0, 100.0) as f32);
+    }
+}
+
+impl Component for MockComponent {
+    type TemperatureReading = TemperatureSnapshot;
+
+    fn label(&self) -> String {
+        self.current.label()
+    }
+
+    fn id(&self) -> SensorId {
+        self.current.id()
+    }
+
+    fn temperatures(&self) -> Vec<TemperatureSnapshot> {
+        self.current.temperatures()
+    }
+
+    fn sensors(&self) -> Vec<SensorReading> {
+        self.current.sensors()
+    }
+
+    fn battery(&self) -> Option<BatteryState> {
+        self.current.battery()
+    }
+
+    fn percentage(&self) -> Option<f32> {
+        self.current.percentage()
+    }
+
+    fn rpm(&self) -> Option<f64> {
+        self.current.rpm()
+    }
+
+    fn component_type(&self) -> ComponentType {
+        self.current.component_type()
+    }
+
+    fn reset_history(&mut self) -> Result<(), Error> {
+        for reading in &mut self.current.temperatures {
+            reading.max = reading.temperature;
+            reading.lowest = Some(reading.temperature);
+        }
+
+        Ok(())
+    }
+}
+
+/// An [`Interface`] of synthetic components described by a [`Scenario`]. Each refresh samples
+/// every signal at the time elapsed since the provider was created.
+#[derive(Clone, Debug)]
+pub struct MockComponents {
+    system: SystemInfo,
+    components: Vec<MockComponent>,
+    started: Instant,
+}
+
+impl MockComponents {
+    /// Creates the components of the given scenario, with their readings sampled at the start of
+    /// their signals.
+    #[must_use]
+    pub fn new(scenario: Scenario) -> Self {
+        Self {
+            system: scenario.system,
+            components: scenario
+                .components
+                .into_iter()
+                .map(MockComponent::new)
+                .collect(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Creates the components of the scenario in the given TOML file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(Self::new(Scenario::load(path)?))
+    }
+
+    /// Samples every signal at the given time since the start of the scenario, regardless of how
+    /// much time has actually passed. This is useful to step through a scenario.
+    pub fn refresh_at(&mut self, elapsed: Duration) {
+        for component in &mut self.components {
+            component.sample(elapsed);
+        }
+    }
+}
+
+/// Creates the components of the built-in demo scenario.
+impl Default for MockComponents {
+    fn default() -> Self {
+        Self::new(Scenario::default())
+    }
+}
+
+impl Interface for MockComponents {
+    type Component = MockComponent;
+
+    fn thermal_components(&self) -> Vec<&Self::Component> {
+        self.components
+            .iter()
+            .filter(|c| !c.spec.temperatures.is_empty())
+            .collect()
+    }
+
+    fn thermal_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        self.components
+            .iter_mut()
+            .filter(|c| !c.spec.temperatures.is_empty())
+            .collect()
+    }
+
+    fn sensor_components(&self) -> Vec<&Self::Component> {
+        self.components
+            .iter()
+            .filter(|c| !c.spec.sensors.is_empty())
+            .collect()
+    }
+
+    fn fan_components(&self) -> Vec<&Self::Component> {
+        self.components
+            .iter()
+            .filter(|c| c.spec.rpm.is_some())
+            .collect()
+    }
+
+    fn fan_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        self.components
+            .iter_mut()
+            .filter(|c| c.spec.rpm.is_some())
+            .collect()
+    }
+
+    fn battery_components(&self) -> Vec<&Self::Component> {
+        self.components
+            .iter()
+            .filter(|c| c.spec.battery.is_some())
+            .collect()
+    }
+
+    fn battery_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        self.components
+            .iter_mut()
+            .filter(|c| c.spec.battery.is_some())
+            .collect()
+    }
+
+    fn os_name(&self) -> String {
+        self.system.os_name.clone()
+    }
+
+    fn cpu_name(&self) -> String {
+        self.system.cpu_name.clone()
+    }
+
+    fn gpu_name(&self) -> Option<String> {
+        self.system.gpu_name.clone()
+    }
+
+    fn device_model_name(&self) -> String {
+        self.system.device_model_name.clone()
+    }
+
+    fn kernel_version(&self) -> Option<String> {
+        self.system.kernel_version.clone()
+    }
+
+    fn board_vendor(&self) -> Option<String> {
+        self.system.board_vendor.clone()
+    }
+
+    fn board_name(&self) -> Option<String> {
+        self.system.board_name.clone()
+    }
+
+    fn bios_version(&self) -> Option<String> {
+        self.system.bios_version.clone()
+    }
+
+    fn core_count(&self) -> Option<usize> {
+        self.system.core_count
+    }
+
+    fn thread_count(&self) -> Option<usize> {
+        self.system.thread_count
+    }
+
+    fn refresh(&mut self) -> Result<(), Error> {
+        self.refresh_at(self.started.elapsed());
+        Ok(())
+    }
+}
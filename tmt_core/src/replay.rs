@@ -0,0 +1,406 @@
+//! Recording of sensor sessions to files, and an [`Interface`] that plays them back.
+//!
+//! A recording is a line of JSON with the format, version and system information, followed by a
+//! line of JSON for every recorded refresh. To keep recordings compact, each refresh only holds
+//! the components that changed since the previous one, and the IDs of those that went away.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ComponentSnapshot, Error, ErrorKind, Interface, SensorId, Snapshot, SystemInfo};
+
+/// The name of the format, which the first line of a recording starts with.
+const FORMAT: &str = "tmtrec";
+
+/// The version of the format written by [`Recorder`]. Recordings of other versions can't be
+/// replayed.
+pub const VERSION: u32 = 1;
+
+/// The first line of a recording.
+#[derive(Debug, Deserialize, Serialize)]
+struct Header {
+    format: String,
+    version: u32,
+    started_at: SystemTime,
+    system: SystemInfo,
+}
+
+/// The part of the first line of a recording that every version shares.
+#[derive(Debug, Deserialize)]
+struct Format {
+    format: String,
+    version: u32,
+}
+
+/// A recorded refresh.
+#[derive(Debug, Deserialize, Serialize)]
+struct Frame {
+    /// The time since the start of the recording, in milliseconds.
+    at: u64,
+    /// The components that were added or changed since the previous frame.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    changed: Vec<ComponentSnapshot>,
+    /// The IDs of the components that went away since the previous frame.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    removed: Vec<SensorId>,
+}
+
+impl Frame {
+    /// Applies this frame to the components of the previous one.
+    fn apply(&self, components: &mut Vec<ComponentSnapshot>) {
+        components.retain(|c| !self.removed.contains(&c.id));
+
+        for component in &self.changed {
+            match components.iter_mut().find(|c| c.id == component.id) {
+                Some(existing) => existing.clone_from(component),
+                None => components.push(component.clone()),
+            }
+        }
+    }
+}
+
+fn invalid_data(line: usize, message: impl std::fmt::Display) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("line {}: {}", line, message),
+    )
+}
+
+/// Writes [`Snapshot`]s to a recording, flushing after each one so the recording stays readable
+/// if the process is killed.
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    writer: W,
+    started_at: Option<SystemTime>,
+    components: Vec<ComponentSnapshot>,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Creates a recording at the given path, replacing any existing file.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Creates a recording written to the given writer. Nothing is written until the first
+    /// snapshot is recorded, whose system information is the one of the whole recording.
+    pub const fn new(writer: W) -> Self {
+        Self {
+            writer,
+            started_at: None,
+            components: Vec::new(),
+        }
+    }
+
+    /// Records the given snapshot, at its capture time.
+    pub fn record(&mut self, snapshot: &Snapshot) -> Result<(), Error> {
+        let started_at = match self.started_at {
+            Some(started_at) => started_at,
+            None => {
+                self.write(&Header {
+                    format: FORMAT.to_string(),
+                    version: VERSION,
+                    started_at: snapshot.captured_at,
+                    system: snapshot.system.clone(),
+                })?;
+                *self.started_at.insert(snapshot.captured_at)
+            }
+        };
+
+        let frame = Frame {
+            at: snapshot
+                .captured_at
+                .duration_since(started_at)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            changed: snapshot
+                .components
+                .iter()
+                .filter(|c| !self.components.contains(c))
+                .cloned()
+                .collect(),
+            removed: self
+                .components
+                .iter()
+                .filter(|c| !snapshot.components.iter().any(|new| new.id == c.id))
+                .map(|c| c.id.clone())
+                .collect(),
+        };
+        self.write(&frame)?;
+        self.components.clone_from(&snapshot.components);
+
+        Ok(())
+    }
+
+    /// The writer the recording is written to.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write(&mut self, line: &impl Serialize) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.writer, line)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// How a [`ReplayComponents`] advances through its recording on each refresh.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Playback {
+    /// Plays the recording back as it was recorded, sped up by the given factor, e.g. `1.0` for
+    /// real time or `4.0` for four times as fast.
+    Speed(f64),
+    /// Advances by one recorded refresh, however long ago the last refresh was.
+    Stepped,
+}
+
+/// An [`Interface`] that plays back a recording written by a [`Recorder`]. Its components are the
+/// ones of the current frame of the recording, and it stops at the last frame.
+#[derive(Debug)]
+pub struct ReplayComponents {
+    system: SystemInfo,
+    started_at: SystemTime,
+    frames: Vec<Frame>,
+    position: usize,
+    components: Vec<ComponentSnapshot>,
+    playback: Playback,
+    started: Instant,
+}
+
+impl ReplayComponents {
+    /// Reads a recording, positioned at its first frame.
+    pub fn new(reader: impl BufRead, playback: Playback) -> Result<Self, Error> {
+        let mut lines = reader.lines();
+        let header = lines
+            .next()
+            .ok_or_else(|| invalid_data(1, "empty recording"))??;
+        let format = serde_json::from_str::<Format>(&header)
+            .ok()
+            .filter(|format| format.format == FORMAT)
+            .ok_or_else(|| invalid_data(1, "not a tmt recording"))?;
+
+        if format.version != VERSION {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!(
+                    "recording version {} is not supported, expected {}",
+                    format.version, VERSION
+                ),
+            ));
+        }
+
+        let header = serde_json::from_str::<Header>(&header).map_err(|err| invalid_data(1, err))?;
+
+        let frames = lines
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str::<Frame>(&line?).map_err(|err| invalid_data(i + 2, err))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut replay = Self {
+            system: header.system,
+            started_at: header.started_at,
+            frames,
+            position: 0,
+            components: Vec::new(),
+            playback,
+            started: Instant::now(),
+        };
+        replay.step();
+        Ok(replay)
+    }
+
+    /// Reads the recording at the given path, positioned at its first frame.
+    pub fn load(path: impl AsRef<Path>, playback: Playback) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|err| Error::new(err.kind().into(), format!("{}: {}", path.display(), err)))?;
+
+        Self::new(BufReader::new(file), playback)
+            .map_err(|err| Error::new(err.kind(), format!("{}: {}", path.display(), err)))
+    }
+
+    /// Advances to the next frame. Returns `false` if the recording is already at its last frame.
+    pub fn step(&mut self) -> bool {
+        match self.frames.get(self.position) {
+            Some(frame) => {
+                frame.apply(&mut self.components);
+                self.position += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves to the last frame recorded at or before the given time since the start of the
+    /// recording, or to the first frame if there is none.
+    pub fn seek(&mut self, elapsed: Duration) {
+        let at = elapsed.as_millis() as u64;
+
+        if self.position > 1 && self.frames[self.position - 1].at > at {
+            self.position = 0;
+            self.components.clear();
+            self.step();
+        }
+
+        while self
+            .frames
+            .get(self.position)
+            .is_some_and(|frame| frame.at <= at)
+        {
+            self.step();
+        }
+    }
+
+    /// The time of the current frame since the start of the recording.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.position
+            .checked_sub(1)
+            .map_or(Duration::ZERO, |i| Duration::from_millis(self.frames[i].at))
+    }
+
+    /// The length of the recording.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.frames
+            .last()
+            .map_or(Duration::ZERO, |frame| Duration::from_millis(frame.at))
+    }
+
+    /// When the current frame was recorded.
+    #[must_use]
+    pub fn captured_at(&self) -> SystemTime {
+        self.started_at + self.elapsed()
+    }
+
+    /// The number of frames in the recording.
+    #[must_use]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether the recording is at its last frame.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.frames.len()
+    }
+}
+
+impl Interface for ReplayComponents {
+    type Component = ComponentSnapshot;
+
+    fn thermal_components(&self) -> Vec<&Self::Component> {
+        self.components
+            .iter()
+            .filter(|c| !c.temperatures.is_empty())
+            .collect()
+    }
+
+    fn thermal_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        self.components
+            .iter_mut()
+            .filter(|c| !c.temperatures.is_empty())
+            .collect()
+    }
+
+    fn sensor_components(&self) -> Vec<&Self::Component> {
+        self.components
+            .iter()
+            .filter(|c| !c.sensors.is_empty())
+            .collect()
+    }
+
+    fn fan_components(&self) -> Vec<&Self::Component> {
+        self.components.iter().filter(|c| c.rpm.is_some()).collect()
+    }
+
+    fn fan_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        self.components
+            .iter_mut()
+            .filter(|c| c.rpm.is_some())
+            .collect()
+    }
+
+    fn battery_components(&self) -> Vec<&Self::Component> {
+        self.components
+            .iter()
+            .filter(|c| c.battery.is_some())
+            .collect()
+    }
+
+    fn battery_components_mut(&mut self) -> Vec<&mut Self::Component> {
+        self.components
+            .iter_mut()
+            .filter(|c| c.battery.is_some())
+            .collect()
+    }
+
+    fn os_name(&self) -> String {
+        self.system.os_name.clone()
+    }
+
+    fn cpu_name(&self) -> String {
+        self.system.cpu_name.clone()
+    }
+
+    fn gpu_name(&self) -> Option<String> {
+        self.system.gpu_name.clone()
+    }
+
+    fn device_model_name(&self) -> String {
+        self.system.device_model_name.clone()
+    }
+
+    fn kernel_version(&self) -> Option<String> {
+        self.system.kernel_version.clone()
+    }
+
+    fn board_vendor(&self) -> Option<String> {
+        self.system.board_vendor.clone()
+    }
+
+    fn board_name(&self) -> Option<String> {
+        self.system.board_name.clone()
+    }
+
+    fn bios_version(&self) -> Option<String> {
+        self.system.bios_version.clone()
+    }
+
+    fn core_count(&self) -> Option<usize> {
+        self.system.core_count
+    }
+
+    fn thread_count(&self) -> Option<usize> {
+        self.system.thread_count
+    }
+
+    /// A snapshot of the current frame, captured at the time it was recorded.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            captured_at: self.captured_at(),
+            ..Snapshot::capture(self)
+        }
+    }
+
+    fn refresh(&mut self) -> Result<(), Error> {
+        match self.playback {
+            Playback::Speed(speed) => self.seek(self.started.elapsed().mul_f64(speed)),
+            Playback::Stepped => {
+                self.step();
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+//! A point-in-time copy of everything an [`Interface`] knows, as plain data that can be stored,
+//! compared or sent elsewhere without holding on to the interface.
+
+use std::collections::HashSet;
+use std::time::SystemTime;
+
+use crate::{
+    BatteryState, Component, ComponentType, CpuLocation, Interface, ReadingStatus, SensorId,
+    SensorReading, Temperature, TemperatureAlarms, TemperatureReading,
+};
+
+/// Everything an [`Interface`] knows at one point in time: the system information, and every
+/// component along with all of its readings. Create one with [`Interface::snapshot`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// When the snapshot was captured.
+    pub captured_at: SystemTime,
+    /// The system the snapshot was captured on.
+    pub system: SystemInfo,
+    /// Every thermal, fan, battery and sensor component, each only once.
+    pub components: Vec<ComponentSnapshot>,
+}
+
+impl Snapshot {
+    /// Captures the current state of the given interface. This does not refresh it first.
+    #[must_use]
+    pub fn capture<I: Interface + ?Sized>(interface: &I) -> Self {
+        let mut seen = HashSet::new();
+        let components = interface
+            .thermal_components()
+            .into_iter()
+            .chain(interface.fan_components())
+            .chain(interface.battery_components())
+            .chain(interface.sensor_components())
+            .filter(|c| seen.insert(c.id()))
+            .map(ComponentSnapshot::capture)
+            .collect();
+
+        Self {
+            captured_at: SystemTime::now(),
+            system: SystemInfo::capture(interface),
+            components,
+        }
+    }
+
+    /// The component with the given ID, or the component the reading with the given ID belongs
+    /// to.
+    #[must_use]
+    pub fn component_by_id(&self, id: &SensorId) -> Option<&ComponentSnapshot> {
+        let component = id.component();
+
+        self.components
+            .iter()
+            .find(|c| c.id == *id)
+            .or_else(|| self.components.iter().find(|c| c.id == component))
+    }
+
+    /// The temperature reading with the given ID, if any.
+    #[must_use]
+    pub fn temperature_by_id(&self, id: &SensorId) -> Option<&TemperatureSnapshot> {
+        self.component_by_id(id)?
+            .temperatures
+            .iter()
+            .find(|reading| reading.id == *id)
+    }
+}
+
+/// What an [`Interface`] knows about the system it runs on.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemInfo {
+    /// See [`Interface::os_name`].
+    pub os_name: String,
+    /// See [`Interface::cpu_name`].
+    pub cpu_name: String,
+    /// See [`Interface::gpu_name`].
+    pub gpu_name: Option<String>,
+    /// See [`Interface::device_model_name`].
+    pub device_model_name: String,
+    /// See [`Interface::kernel_version`].
+    pub kernel_version: Option<String>,
+    /// See [`Interface::board_vendor`].
+    pub board_vendor: Option<String>,
+    /// See [`Interface::board_name`].
+    pub board_name: Option<String>,
+    /// See [`Interface::bios_version`].
+    pub bios_version: Option<String>,
+    /// See [`Interface::core_count`].
+    pub core_count: Option<usize>,
+    /// See [`Interface::thread_count`].
+    pub thread_count: Option<usize>,
+}
+
+impl SystemInfo {
+    /// Reads the system information of the given interface.
+    #[must_use]
+    pub fn capture<I: Interface + ?Sized>(interface: &I) -> Self {
+        Self {
+            os_name: interface.os_name(),
+            cpu_name: interface.cpu_name(),
+            gpu_name: interface.gpu_name(),
+            device_model_name: interface.device_model_name(),
+            kernel_version: interface.kernel_version(),
+            board_vendor: interface.board_vendor(),
+            board_name: interface.board_name(),
+            bios_version: interface.bios_version(),
+            core_count: interface.core_count(),
+            thread_count: interface.thread_count(),
+        }
+    }
+}
+
+/// A [`Component`] and all of its readings at one point in time.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentSnapshot {
+    /// See [`Component::id`].
+    pub id: SensorId,
+    /// See [`Component::label`].
+    pub label: String,
+    /// See [`Component::component_type`].
+    pub component_type: ComponentType,
+    /// See [`Component::temperatures`].
+    pub temperatures: Vec<TemperatureSnapshot>,
+    /// See [`Component::sensors`].
+    pub sensors: Vec<SensorReading>,
+    /// See [`Component::battery`].
+    pub battery: Option<BatteryState>,
+    /// See [`Component::percentage`].
+    pub percentage: Option<f32>,
+    /// See [`Component::rpm`].
+    pub rpm: Option<f64>,
+}
+
+impl ComponentSnapshot {
+    /// Copies the current readings of the given component.
+    #[must_use]
+    pub fn capture<C: Component + ?Sized>(component: &C) -> Self {
+        Self {
+            id: component.id(),
+            label: component.label(),
+            component_type: component.component_type(),
+            temperatures: component
+                .temperatures()
+                .iter()
+                .map(TemperatureSnapshot::capture)
+                .collect(),
+            sensors: component.sensors(),
+            battery: component.battery(),
+            percentage: component.percentage(),
+            rpm: component.rpm(),
+        }
+    }
+}
+
+/// A [`TemperatureReading`] and all of its thresholds at one point in time.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemperatureSnapshot {
+    /// See [`TemperatureReading::id`].
+    pub id: SensorId,
+    /// See [`TemperatureReading::label`].
+    pub label: String,
+    /// See [`TemperatureReading::temperature`].
+    pub temperature: Temperature,
+    /// See [`TemperatureReading::max`].
+    pub max: Temperature,
+    /// See [`TemperatureReading::high`].
+    pub high: Temperature,
+    /// See [`TemperatureReading::critical`].
+    pub critical: Temperature,
+    /// See [`TemperatureReading::min`].
+    pub min: Option<Temperature>,
+    /// See [`TemperatureReading::lowest`].
+    pub lowest: Option<Temperature>,
+    /// See [`TemperatureReading::low_critical`].
+    pub low_critical: Option<Temperature>,
+    /// See [`TemperatureReading::emergency`].
+    pub emergency: Option<Temperature>,
+    /// See [`TemperatureReading::high_hysteresis`].
+    pub high_hysteresis: Option<Temperature>,
+    /// See [`TemperatureReading::critical_hysteresis`].
+    pub critical_hysteresis: Option<Temperature>,
+    /// See [`TemperatureReading::alarms`].
+    pub alarms: TemperatureAlarms,
+    /// See [`TemperatureReading::status`].
+    pub status: ReadingStatus,
+    /// See [`TemperatureReading::usage`].
+    pub usage: Option<f32>,
+    /// See [`TemperatureReading::cpu_location`].
+    pub cpu_location: Option<CpuLocation>,
+}
+
+impl TemperatureSnapshot {
+    /// Copies the given reading.
+    #[must_use]
+    pub fn capture<R: TemperatureReading + ?Sized>(reading: &R) -> Self {
+        Self {
+            id: reading.id(),
+            label: reading.label(),
+            temperature: reading.temperature(),
+            max: reading.max(),
+            high: reading.high(),
+            critical: reading.critical(),
+            min: reading.min(),
+            lowest: reading.lowest(),
+            low_critical: reading.low_critical(),
+            emergency: reading.emergency(),
+            high_hysteresis: reading.high_hysteresis(),
+            critical_hysteresis: reading.critical_hysteresis(),
+            alarms: reading.alarms(),
+            status: reading.status(),
+            usage: reading.usage(),
+            cpu_location: reading.cpu_location(),
+        }
+    }
+}
+
+/// A captured component, so snapshots can stand in for live components, e.g. when replaying them.
+impl Component for ComponentSnapshot {
+    type TemperatureReading = TemperatureSnapshot;
+
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn id(&self) -> SensorId {
+        self.id.clone()
+    }
+
+    fn temperatures(&self) -> Vec<TemperatureSnapshot> {
+        self.temperatures.clone()
+    }
+
+    fn sensors(&self) -> Vec<SensorReading> {
+        self.sensors.clone()
+    }
+
+    fn battery(&self) -> Option<BatteryState> {
+        self.battery.clone()
+    }
+
+    fn percentage(&self) -> Option<f32> {
+        self.percentage
+    }
+
+    fn rpm(&self) -> Option<f64> {
+        self.rpm
+    }
+
+    fn component_type(&self) -> ComponentType {
+        self.component_type
+    }
+}
+
+impl TemperatureReading for TemperatureSnapshot {
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn id(&self) -> SensorId {
+        self.id.clone()
+    }
+
+    fn temperature(&self) -> Temperature {
+        self.temperature
+    }
+
+    fn max(&self) -> Temperature {
+        self.max
+    }
+
+    fn high(&self) -> Temperature {
+        self.high
+    }
+
+    fn critical(&self) -> Temperature {
+        self.critical
+    }
+
+    fn min(&self) -> Option<Temperature> {
+        self.min
+    }
+
+    fn lowest(&self) -> Option<Temperature> {
+        self.lowest
+    }
+
+    fn low_critical(&self) -> Option<Temperature> {
+        self.low_critical
+    }
+
+    fn emergency(&self) -> Option<Temperature> {
+        self.emergency
+    }
+
+    fn high_hysteresis(&self) -> Option<Temperature> {
+        self.high_hysteresis
+    }
+
+    fn critical_hysteresis(&self) -> Option<Temperature> {
+        self.critical_hysteresis
+    }
+
+    fn alarms(&self) -> TemperatureAlarms {
+        self.alarms
+    }
+
+    fn status(&self) -> ReadingStatus {
+        self.status.clone()
+    }
+
+    fn usage(&self) -> Option<f32> {
+        self.usage
+    }
+
+    fn cpu_location(&self) -> Option<CpuLocation> {
+        self.cpu_location.clone()
+    }
+}
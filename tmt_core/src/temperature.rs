@@ -0,0 +1,175 @@
+//! A signed temperature value, convertible between Celsius, Fahrenheit and Kelvin.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A unit of temperature.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum TemperatureUnit {
+    /// Degrees Celsius.
+    #[default]
+    Celsius,
+    /// Degrees Fahrenheit.
+    Fahrenheit,
+    /// Kelvin.
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    /// The symbol of this unit, e.g. `°C`.
+    #[must_use]
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+            Self::Kelvin => "K",
+        }
+    }
+}
+
+impl fmt::Display for TemperatureUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for TemperatureUnit {
+    type Err = String;
+
+    /// Parses a unit from its letter or name, e.g. `F` or `fahrenheit`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "c" | "celsius" => Ok(Self::Celsius),
+            "f" | "fahrenheit" => Ok(Self::Fahrenheit),
+            "k" | "kelvin" => Ok(Self::Kelvin),
+            _ => Err(format!(
+                "unknown temperature unit {:?}, expected C, F or K",
+                s
+            )),
+        }
+    }
+}
+
+/// A temperature, stored in thousandths of a degree Celsius. This is the resolution hwmon reports
+/// in, so readings are represented exactly, and it can go below zero for ambient probes and
+/// uncalibrated sensors.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Temperature(i32);
+
+impl Temperature {
+    /// Absolute zero, the lowest possible temperature.
+    pub const ABSOLUTE_ZERO: Self = Self(-273_150);
+
+    /// Creates a temperature from thousandths of a degree Celsius.
+    #[must_use]
+    pub const fn from_millidegrees(millidegrees: i32) -> Self {
+        Self(millidegrees)
+    }
+
+    /// Creates a temperature from degrees Celsius, rounded to the nearest thousandth.
+    #[must_use]
+    pub fn from_celsius(celsius: f64) -> Self {
+        Self((celsius * 1000.0).round() as i32)
+    }
+
+    /// Creates a temperature from degrees Fahrenheit, rounded to the nearest thousandth of a
+    /// degree Celsius.
+    #[must_use]
+    pub fn from_fahrenheit(fahrenheit: f64) -> Self {
+        Self::from_celsius((fahrenheit - 32.0) * 5.0 / 9.0)
+    }
+
+    /// Creates a temperature from Kelvin, rounded to the nearest thousandth.
+    #[must_use]
+    pub fn from_kelvin(kelvin: f64) -> Self {
+        Self::from_celsius(kelvin - 273.15)
+    }
+
+    /// Creates a temperature from a value in the given unit.
+    #[must_use]
+    pub fn from_unit(value: f64, unit: TemperatureUnit) -> Self {
+        match unit {
+            TemperatureUnit::Celsius => Self::from_celsius(value),
+            TemperatureUnit::Fahrenheit => Self::from_fahrenheit(value),
+            TemperatureUnit::Kelvin => Self::from_kelvin(value),
+        }
+    }
+
+    /// This temperature in thousandths of a degree Celsius.
+    #[must_use]
+    pub const fn millidegrees(self) -> i32 {
+        self.0
+    }
+
+    /// This temperature in degrees Celsius.
+    #[must_use]
+    pub fn celsius(self) -> f64 {
+        f64::from(self.0) / 1000.0
+    }
+
+    /// This temperature in degrees Fahrenheit.
+    #[must_use]
+    pub fn fahrenheit(self) -> f64 {
+        f64::from(self.0) * 9.0 / 5000.0 + 32.0
+    }
+
+    /// This temperature in Kelvin.
+    #[must_use]
+    pub fn kelvin(self) -> f64 {
+        f64::from(self.0 - Self::ABSOLUTE_ZERO.0) / 1000.0
+    }
+
+    /// This temperature in the given unit.
+    #[must_use]
+    pub fn to_unit(self, unit: TemperatureUnit) -> f64 {
+        match unit {
+            TemperatureUnit::Celsius => self.celsius(),
+            TemperatureUnit::Fahrenheit => self.fahrenheit(),
+            TemperatureUnit::Kelvin => self.kelvin(),
+        }
+    }
+
+    /// Formats this temperature in the given unit, e.g. `format!("{:.1}", t.display(unit))`.
+    #[must_use]
+    pub const fn display(self, unit: TemperatureUnit) -> DisplayTemperature {
+        DisplayTemperature(self, unit)
+    }
+}
+
+/// Formats a temperature in degrees Celsius, e.g. `48.5 °C`. The precision, if given, applies
+/// to the number.
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.display(TemperatureUnit::Celsius).fmt(f)
+    }
+}
+
+/// Serialized as degrees Celsius, e.g. `52.125`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Temperature {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.celsius())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Temperature {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Self::from_celsius)
+    }
+}
+
+/// A [`Temperature`] formatted in a given unit. See [`Temperature::display`].
+#[derive(Copy, Clone, Debug)]
+pub struct DisplayTemperature(Temperature, TemperatureUnit);
+
+impl fmt::Display for DisplayTemperature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = self.0.to_unit(self.1);
+
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*} {}", precision, value, self.1),
+            None => write!(f, "{} {}", value, self.1),
+        }
+    }
+}
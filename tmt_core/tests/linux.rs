@@ -0,0 +1,1561 @@
+//! Exercises the Linux backend against captured sysfs/procfs trees in `tests/fixtures`.
+
+#![cfg(target_os = "linux")]
+
+use std::error::Error as _;
+use std::path::PathBuf;
+use tmt_core::linux::{
+    BusType, ChipName, CoolingBinding, FanControlMode, LinuxError, SensorsConfig, ThermalZoneMode,
+    ThrottleReasons, TripPoint, TripPointType,
+};
+use tmt_core::{
+    BatteryStatus, Component, ComponentType, CpuLocation, Error, ErrorKind, Interface, Provider,
+    ReadingStatus, SensorId, SensorKind, SensorReading, Temperature, TemperatureAlarms,
+    TemperatureReading,
+};
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn fixture(name: &str) -> Provider {
+    let mut provider = Provider::with_root(fixture_path(name)).expect("failed to load fixture");
+    provider.refresh().expect("failed to refresh fixture");
+    provider
+}
+
+/// Creates a writable scratch root for the test with the given name, either as a copy of the given
+/// fixture, preserving its symlinks, or empty. Tests remove it once they are done.
+fn scratch_root(name: &str, fixture: Option<&str>) -> PathBuf {
+    fn copy(from: &std::path::Path, to: &std::path::Path) {
+        std::fs::create_dir_all(to).unwrap();
+
+        for entry in std::fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            let target = to.join(entry.file_name());
+            let file_type = entry.file_type().unwrap();
+
+            if file_type.is_symlink() {
+                let link = std::fs::read_link(entry.path()).unwrap();
+                std::os::unix::fs::symlink(link, target).unwrap();
+            } else if file_type.is_dir() {
+                copy(&entry.path(), &target);
+            } else {
+                std::fs::copy(entry.path(), target).unwrap();
+            }
+        }
+    }
+
+    let root = std::env::temp_dir().join(format!("tmt-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    match fixture {
+        Some(fixture) => copy(&fixture_path(fixture), &root),
+        None => std::fs::create_dir_all(&root).unwrap(),
+    }
+    root
+}
+
+fn reading(provider: &Provider, component: &str, label: &str) -> f64 {
+    provider
+        .thermal_components()
+        .into_iter()
+        .filter(|c| c.label() == component)
+        .flat_map(|c| c.temperatures())
+        .find(|r| r.label() == label)
+        .unwrap_or_else(|| panic!("no reading {:?} in {:?}", label, component))
+        .temperature()
+        .celsius()
+}
+
+#[test]
+fn intel_coretemp_laptop() {
+    let provider = fixture("intel-coretemp-laptop");
+
+    assert_eq!(provider.os_name(), "Fedora Linux 36 (Workstation Edition)");
+    assert_eq!(
+        provider.cpu_name(),
+        "Intel(R) Core(TM) i7-7500U CPU @ 2.70GHz"
+    );
+    assert_eq!(provider.device_model_name(), "XPS 13 9360");
+
+    assert_eq!(reading(&provider, "coretemp", "Package id 0"), 48.0);
+    assert_eq!(reading(&provider, "coretemp", "Core 1"), 47.0);
+    assert_eq!(reading(&provider, "nvme", "Composite"), 38.85);
+}
+
+#[test]
+fn ryzen_k10temp_desktop() {
+    let provider = fixture("ryzen-k10temp-desktop");
+
+    assert_eq!(provider.os_name(), "Arch Linux");
+    assert_eq!(provider.cpu_name(), "AMD Ryzen 9 5900X 12-Core Processor");
+    assert_eq!(provider.device_model_name(), "System Product Name");
+
+    assert_eq!(reading(&provider, "k10temp", "Tctl"), 52.125);
+    assert_eq!(reading(&provider, "k10temp", "Tccd2"), 44.5);
+    assert_eq!(reading(&provider, "amdgpu", "junction"), 47.0);
+}
+
+#[test]
+fn rpi_thermal_zone() {
+    let provider = fixture("rpi-thermal-zone");
+
+    assert_eq!(provider.os_name(), "Debian GNU/Linux 12 (bookworm)");
+    assert_eq!(provider.cpu_name(), "ARM Cortex-A72");
+    assert_eq!(
+        provider.device_model_name(),
+        "Raspberry Pi 4 Model B Rev 1.1"
+    );
+    assert_eq!(reading(&provider, "cpu-thermal", "cpu-thermal"), 48.686);
+}
+
+#[test]
+fn root_from_env() {
+    // The environment is shared by every test running in parallel, so TMT_ROOT is only set for
+    // this test run again in a process of its own.
+    let root = fixture_path("rpi-thermal-zone");
+    if std::env::var_os("TMT_ROOT") != Some(root.clone().into()) {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "root_from_env", "--test-threads", "1"])
+            .env("TMT_ROOT", &root)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success() && stdout.contains("1 passed"),
+            "{}",
+            stdout
+        );
+        return;
+    }
+
+    let provider = Provider::new().expect("failed to load fixture");
+    assert_eq!(provider.os_name(), "Debian GNU/Linux 12 (bookworm)");
+}
+
+#[test]
+fn no_sensors() {
+    // Systems without any sensors, e.g. virtual machines, are not an error.
+    let root = scratch_root("no-sensors", None);
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+
+    assert!(provider.thermal_components().is_empty());
+    assert!(provider.fan_components().is_empty());
+    assert_eq!(provider.gpu_name(), None);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn system_identification() {
+    let laptop = fixture("intel-coretemp-laptop");
+    assert_eq!(
+        laptop.kernel_version().as_deref(),
+        Some("5.19.16-200.fc36.x86_64")
+    );
+    assert_eq!(laptop.board_vendor().as_deref(), Some("Dell Inc."));
+    assert_eq!(laptop.board_name().as_deref(), Some("0839Y6"));
+    assert_eq!(laptop.bios_version().as_deref(), Some("2.21.0"));
+    assert_eq!(laptop.core_count(), Some(2));
+    assert_eq!(laptop.thread_count(), Some(4));
+
+    let desktop = fixture("ryzen-k10temp-desktop");
+    assert_eq!(
+        desktop.board_name().as_deref(),
+        Some("ROG STRIX X570-E GAMING")
+    );
+    assert_eq!(desktop.core_count(), Some(12));
+    assert_eq!(desktop.thread_count(), Some(24));
+
+    let server = fixture("xeon-dual-socket");
+    assert_eq!(server.core_count(), Some(4));
+    assert_eq!(server.thread_count(), Some(8));
+    assert_eq!(server.board_vendor(), None);
+
+    let pi = fixture("rpi-thermal-zone");
+    assert_eq!(pi.kernel_version().as_deref(), Some("6.1.21-v8+"));
+    assert_eq!(pi.bios_version(), None);
+    assert_eq!(pi.core_count(), Some(4));
+
+    // A system without any of the identifying files still starts.
+    let root = scratch_root("empty", None);
+    let provider = Provider::with_root(&root).unwrap();
+    assert_eq!(provider.os_name(), "Linux");
+    assert_eq!(provider.cpu_name(), "Unknown");
+    assert_eq!(provider.device_model_name(), "Unknown");
+    assert_eq!(provider.kernel_version(), None);
+    assert_eq!(provider.thread_count(), None);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn thermal_zones_merged_with_hwmon() {
+    let provider = fixture("intel-coretemp-laptop");
+    let components = provider
+        .thermal_components()
+        .into_iter()
+        .map(|c| (c.label(), c.source().to_string()))
+        .collect::<Vec<_>>();
+
+    // acpitz is exposed both as thermal_zone0 and as its hwmon0 child, and should only be shown
+    // once. The other zones have no hwmon counterpart.
+    assert_eq!(
+        components
+            .iter()
+            .filter(|(label, _)| label == "acpitz")
+            .collect::<Vec<_>>(),
+        [&("acpitz".to_string(), "hwmon".to_string())],
+    );
+    assert!(components.contains(&("x86_pkg_temp".to_string(), "thermal zone".to_string())));
+    assert!(components.contains(&("iwlwifi_1".to_string(), "thermal zone".to_string())));
+    assert_eq!(reading(&provider, "x86_pkg_temp", "x86_pkg_temp"), 48.0);
+}
+
+#[test]
+fn component_classification() {
+    let component_type = |provider: &Provider, label: &str| {
+        provider
+            .thermal_components()
+            .into_iter()
+            .find(|c| c.label() == label)
+            .unwrap_or_else(|| panic!("no component {:?}", label))
+            .component_type()
+    };
+
+    let mut provider = fixture("intel-coretemp-laptop");
+    assert_eq!(component_type(&provider, "coretemp"), ComponentType::Cpu);
+    assert_eq!(
+        component_type(&provider, "x86_pkg_temp"),
+        ComponentType::Cpu
+    );
+    assert_eq!(component_type(&provider, "nvme"), ComponentType::Storage);
+    assert_eq!(
+        component_type(&provider, "acpitz"),
+        ComponentType::Motherboard
+    );
+    assert_eq!(
+        component_type(&provider, "iwlwifi_1"),
+        ComponentType::Network
+    );
+    assert_eq!(component_type(&provider, "dell_smm"), ComponentType::System);
+
+    provider.classify("dell_*", ComponentType::Motherboard);
+    assert_eq!(
+        component_type(&provider, "dell_smm"),
+        ComponentType::Motherboard
+    );
+
+    let provider = fixture("ryzen-k10temp-desktop");
+    assert_eq!(component_type(&provider, "k10temp"), ComponentType::Cpu);
+    assert_eq!(component_type(&provider, "amdgpu"), ComponentType::Gpu);
+    assert_eq!(
+        component_type(&provider, "nct6798"),
+        ComponentType::Motherboard
+    );
+
+    let provider = fixture("rpi-thermal-zone");
+    assert_eq!(component_type(&provider, "cpu-thermal"), ComponentType::Cpu);
+}
+
+#[test]
+fn electrical_sensors() {
+    let provider = fixture("ryzen-k10temp-desktop");
+    let sensors = |label: &str| -> Vec<SensorReading> {
+        provider
+            .sensor_components()
+            .into_iter()
+            .find(|c| c.label() == label)
+            .unwrap_or_else(|| panic!("no sensor component {:?}", label))
+            .sensors()
+    };
+
+    let gpu = sensors("amdgpu");
+    let labels = gpu.iter().map(|s| s.label.as_str()).collect::<Vec<_>>();
+    assert_eq!(labels, ["vddgfx", "vddnb", "PPT"]);
+    assert_eq!(gpu[0].kind, SensorKind::Voltage);
+    assert!((gpu[0].value - 0.737).abs() < 1e-9);
+    assert_eq!(gpu[2].kind, SensorKind::Power);
+    assert_eq!(gpu[2].kind.unit(), "W");
+    assert!((gpu[2].value - 12.0).abs() < 1e-9);
+    assert_eq!(gpu[2].max, Some(203.0));
+
+    let board = sensors("nct6798");
+    let in2 = board.iter().find(|s| s.label == "in2").unwrap();
+    assert!((in2.value - 3.392).abs() < 1e-9);
+    assert!((in2.min.unwrap() - 2.976).abs() < 1e-9);
+    assert!((in2.max.unwrap() - 3.632).abs() < 1e-9);
+    assert!(board.iter().all(|s| s.kind == SensorKind::Voltage));
+
+    // Chips without electrical channels are not sensor components.
+    assert!(provider
+        .sensor_components()
+        .iter()
+        .all(|c| c.label() != "k10temp"));
+
+    // Chips without a name are labelled after their device.
+    let root = scratch_root("unnamed-chip", Some("ryzen-k10temp-desktop"));
+    std::fs::remove_file(root.join("sys/devices/platform/nct6775.656/hwmon/hwmon1/name")).unwrap();
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+    assert!(provider
+        .sensor_components()
+        .iter()
+        .any(|c| c.label() == "nct6775.656"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn stable_sensor_ids() {
+    let provider = fixture("ryzen-k10temp-desktop");
+    let id = SensorId::new("hwmon:k10temp@pci0000:00/0000:00:18.3#temp3");
+
+    let k10temp = provider
+        .component_by_id(&id)
+        .expect("no component for reading id");
+    assert_eq!(k10temp.label(), "k10temp");
+    assert_eq!(
+        k10temp.id().as_str(),
+        "hwmon:k10temp@pci0000:00/0000:00:18.3"
+    );
+    assert_eq!(id.component(), k10temp.id());
+
+    let tccd1 = provider.temperature_by_id(&id).expect("no reading for id");
+    assert_eq!(tccd1.label(), "Tccd1");
+    assert!(provider
+        .temperature_by_id(&SensorId::new(
+            "hwmon:k10temp@pci0000:00/0000:00:18.3#temp9"
+        ))
+        .is_none());
+
+    let fan = SensorId::new("hwmon:nct6798@platform/nct6775.656#fan2");
+    let fan = provider.component_by_id(&fan).expect("no fan for id");
+    assert_eq!(fan.fan().map(|fan| fan.index()), Some(2));
+
+    let board = provider
+        .sensor_components()
+        .into_iter()
+        .find(|c| c.label() == "nct6798")
+        .unwrap();
+    assert_eq!(
+        board.sensors()[2].id.as_str(),
+        "hwmon:nct6798@platform/nct6775.656#in2"
+    );
+
+    // IDs are unique, and don't change when the system is enumerated again. Components with a
+    // single reading, such as thermal zones, share their ID with it.
+    let ids = |provider: &Provider| {
+        let components = provider
+            .thermal_components()
+            .into_iter()
+            .chain(provider.fan_components())
+            .chain(provider.battery_components())
+            .collect::<Vec<_>>();
+        let mut ids = components.iter().map(|c| c.id()).collect::<Vec<_>>();
+        let mut readings = components
+            .iter()
+            .flat_map(|c| c.temperatures())
+            .map(|reading| reading.id())
+            .collect::<Vec<_>>();
+        ids.sort();
+        readings.sort();
+        (ids, readings)
+    };
+
+    for name in [
+        "intel-coretemp-laptop",
+        "rpi-thermal-zone",
+        "xeon-dual-socket",
+    ] {
+        let (mut components, mut readings) = ids(&fixture(name));
+        components.dedup();
+        readings.dedup();
+        assert_eq!(ids(&fixture(name)), (components, readings), "{}", name);
+    }
+
+    let rpi = fixture("rpi-thermal-zone");
+    let zone = SensorId::new("thermal:cpu-thermal@virtual/thermal/thermal_zone0");
+    assert_eq!(rpi.component_by_id(&zone).unwrap().label(), "cpu-thermal");
+    let reading = rpi.thermal_components()[0].temperatures()[0].id();
+    assert_eq!(reading, zone.channel("temp"));
+    assert_eq!(rpi.temperature_by_id(&reading).unwrap().id(), reading);
+    assert_eq!(rpi.component_by_id(&reading).unwrap().id(), zone);
+
+    let laptop = fixture("intel-coretemp-laptop");
+    let battery = laptop.battery_components()[0].id();
+    assert!(battery.as_str().starts_with("power_supply:BAT0@"));
+    let reading = laptop.battery_components()[0].temperatures()[0].id();
+    assert_eq!(reading, battery.channel("temp"));
+}
+
+#[test]
+fn hwmon_limits_and_alarms() {
+    let provider = fixture("xeon-dual-socket");
+    let temperatures = |component: &str| {
+        provider
+            .thermal_components()
+            .into_iter()
+            .find(|c| c.label() == component)
+            .unwrap_or_else(|| panic!("no component {:?}", component))
+            .temperatures()
+    };
+
+    let dimm = &temperatures("jc42")[0];
+    assert_eq!(dimm.min(), Some(Temperature::from_celsius(0.0)));
+    assert_eq!(dimm.high(), Temperature::from_celsius(80.0));
+    assert_eq!(
+        dimm.high_hysteresis(),
+        Some(Temperature::from_celsius(78.5))
+    );
+    assert_eq!(
+        dimm.critical_hysteresis(),
+        Some(Temperature::from_celsius(93.5))
+    );
+    assert_eq!(dimm.lowest(), None);
+    assert_eq!(dimm.alarms(), TemperatureAlarms::MAX);
+
+    let psu = temperatures("dps920ab");
+    let inlet = psu.iter().find(|r| r.label() == "Inlet").unwrap();
+    assert_eq!(inlet.lowest(), Some(Temperature::from_celsius(24.0)));
+    assert_eq!(inlet.max(), Temperature::from_celsius(47.5));
+    assert!(inlet.alarms().is_empty());
+    assert!(psu.iter().any(|r| r.alarms().is_fault()));
+
+    // Readings below zero are valid, even if this one comes from a faulty sensor.
+    let exhaust = psu.iter().find(|r| r.label() == "Exhaust").unwrap();
+    assert_eq!(exhaust.status(), ReadingStatus::Ok);
+    assert_eq!(exhaust.temperature(), Temperature::from_celsius(-40.0));
+
+    // Sensors without any of the attributes have none of the values.
+    let core = &temperatures("coretemp")[0];
+    assert_eq!(core.emergency(), None);
+    assert_eq!(core.low_critical(), None);
+    assert!(core.alarms().is_empty());
+
+    let amdgpu = fixture("ryzen-k10temp-desktop");
+    let edge = amdgpu
+        .thermal_components()
+        .into_iter()
+        .filter(|c| c.label() == "amdgpu")
+        .flat_map(|c| c.temperatures())
+        .find(|r| r.label() == "edge")
+        .unwrap();
+    assert_eq!(edge.emergency(), Some(Temperature::from_celsius(105.0)));
+    assert_eq!(
+        edge.critical_hysteresis(),
+        Some(Temperature::from_celsius(-273.15))
+    );
+}
+
+#[test]
+fn reset_temperature_history() {
+    let root = scratch_root("reset-history", Some("xeon-dual-socket"));
+    let psu = root.join("sys/devices/pci0000:00/0000:00:1f.4/i2c-0/0-0058/hwmon/hwmon4");
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+
+    provider.reset_history().unwrap();
+    for channel in [1, 2] {
+        let path = psu.join(format!("temp{}_reset_history", channel));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "1");
+    }
+
+    // Chips that can't reset their history are left alone.
+    let coretemp = root.join("sys/devices/platform/coretemp.0/hwmon/hwmon1");
+    assert!(!coretemp.join("reset_history").exists());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+fn cpu_locations(provider: &Provider, component: &str) -> Vec<(String, CpuLocation)> {
+    let mut locations = provider
+        .thermal_components()
+        .into_iter()
+        .filter(|c| c.label() == component)
+        .flat_map(|c| c.temperatures())
+        .map(|r| (r.label(), r.cpu_location().expect("no cpu location")))
+        .collect::<Vec<_>>();
+
+    locations.sort_by_key(|(_, location)| (location.package, location.die, location.core));
+    locations
+}
+
+#[test]
+fn cpu_topology() {
+    let laptop = fixture("intel-coretemp-laptop");
+    assert_eq!(laptop.topology().packages(), [0]);
+    assert_eq!(
+        cpu_locations(&laptop, "coretemp")
+            .into_iter()
+            .map(|(label, location)| (label, location.core, location.cpus))
+            .collect::<Vec<_>>(),
+        [
+            ("Package id 0".to_string(), None, vec![0, 1, 2, 3]),
+            ("Core 0".to_string(), Some(0), vec![0, 2]),
+            ("Core 1".to_string(), Some(1), vec![1, 3]),
+        ]
+    );
+
+    let desktop = fixture("ryzen-k10temp-desktop");
+    let locations = cpu_locations(&desktop, "k10temp");
+    let labels = locations
+        .iter()
+        .map(|(l, _)| l.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(labels, ["Tctl", "Tccd1", "Tccd2"]);
+    assert_eq!(locations[0].1.cpus, (0..24).collect::<Vec<_>>());
+    assert_eq!(locations[1].1.die, Some(0));
+    assert_eq!(
+        locations[1].1.cpus,
+        [0, 1, 2, 3, 4, 5, 12, 13, 14, 15, 16, 17]
+    );
+    assert_eq!(locations[2].1.die, Some(1));
+    assert_eq!(
+        locations[2].1.cpus,
+        [6, 7, 8, 9, 10, 11, 18, 19, 20, 21, 22, 23]
+    );
+
+    // Non-CPU chips are not mapped.
+    assert!(desktop
+        .thermal_components()
+        .into_iter()
+        .filter(|c| c.component_type() != ComponentType::Cpu)
+        .flat_map(|c| c.temperatures())
+        .all(|r| r.cpu_location().is_none()));
+
+    // Each socket of a multi-socket server gets its own group.
+    let server = fixture("xeon-dual-socket");
+    assert_eq!(server.topology().packages(), [0, 1]);
+    let locations = cpu_locations(&server, "coretemp");
+    assert_eq!(
+        locations
+            .iter()
+            .map(|(label, location)| (label.as_str(), location.package, location.core))
+            .collect::<Vec<_>>(),
+        [
+            ("Package id 0", 0, None),
+            ("Core 0", 0, Some(0)),
+            ("Core 1", 0, Some(1)),
+            ("Package id 1", 1, None),
+            ("Core 0", 1, Some(0)),
+            ("Core 1", 1, Some(1)),
+        ]
+    );
+    assert_eq!(locations[5].1.cpus, [3, 7]);
+}
+
+#[test]
+fn power_supply_batteries() {
+    let laptop = fixture("intel-coretemp-laptop");
+    let batteries = laptop.battery_components();
+    assert_eq!(batteries.len(), 1);
+
+    let battery = batteries[0];
+    assert_eq!(battery.label(), "BAT0");
+    assert_eq!(battery.component_type(), ComponentType::Battery);
+    assert_eq!(battery.percentage(), Some(76.0));
+    assert_eq!(
+        battery.power_supply().unwrap().model(),
+        Some("DELL TP1GT61")
+    );
+    assert!((battery.temperatures()[0].temperature().celsius() - 31.2).abs() < 1e-9);
+
+    let state = battery.battery().unwrap();
+    assert_eq!(state.status, BatteryStatus::Discharging);
+    assert!((state.energy.unwrap() - 45.12).abs() < 1e-9);
+    assert!((state.energy_full.unwrap() - 59.37).abs() < 1e-9);
+    assert!((state.power.unwrap() - 8.46).abs() < 1e-9);
+    assert_eq!(state.time_to_empty.unwrap().as_secs() / 60, 320);
+    assert_eq!(state.time_to_full, None);
+
+    // Batteries are not thermal components, so their temperature isn't counted twice.
+    assert!(laptop
+        .thermal_components()
+        .iter()
+        .all(|c| c.component_type() != ComponentType::Battery));
+
+    // Peripheral batteries, such as a wireless mouse's, are not system batteries.
+    assert!(fixture("ryzen-k10temp-desktop")
+        .battery_components()
+        .is_empty());
+
+    // Batteries that report charge rather than energy are converted using their voltage.
+    let root = scratch_root("battery", None);
+    let battery_dir = root.join("sys/class/power_supply/BAT1");
+    std::fs::create_dir_all(&battery_dir).unwrap();
+    for (attribute, value) in [
+        ("type", "Battery"),
+        ("status", "Charging"),
+        ("charge_now", "2000000"),
+        ("charge_full", "4000000"),
+        ("current_now", "1000000"),
+        ("voltage_now", "12000000"),
+        ("voltage_min_design", "11000000"),
+    ] {
+        std::fs::write(battery_dir.join(attribute), format!("{}\n", value)).unwrap();
+    }
+
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+    let battery = provider.battery_components()[0];
+    let state = battery.battery().unwrap();
+    assert_eq!(state.status, BatteryStatus::Charging);
+    assert!((state.energy.unwrap() - 22.0).abs() < 1e-9);
+    assert!((state.power.unwrap() - 12.0).abs() < 1e-9);
+    assert_eq!(state.time_to_full.unwrap().as_secs(), 6600);
+    assert_eq!(battery.percentage(), Some(50.0));
+    assert!(battery.temperatures().is_empty());
+
+    // After recalibrating, a charging battery may hold more energy than when it is full.
+    std::fs::write(battery_dir.join("charge_now"), "4200000\n").unwrap();
+    provider.refresh().unwrap();
+    let state = provider.battery_components()[0].battery().unwrap();
+    assert!(state.energy.unwrap() > state.energy_full.unwrap());
+    assert_eq!(state.time_to_full, None);
+    assert_eq!(provider.battery_components()[0].percentage(), Some(100.0));
+
+    // Nonsensical readings don't give nonsensical capacities or overflow.
+    std::fs::write(battery_dir.join("charge_full"), "0\n").unwrap();
+    std::fs::write(battery_dir.join("temp"), format!("{}\n", i32::MAX)).unwrap();
+    provider.refresh().unwrap();
+    let battery = provider.battery_components()[0];
+    assert_eq!(battery.percentage(), None);
+    assert!(battery.temperatures()[0].temperature().celsius() > 1e6);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn hwmon_fans() {
+    let provider = fixture("intel-coretemp-laptop");
+    let fans = provider.fan_components();
+
+    assert_eq!(fans.len(), 1);
+    assert_eq!(fans[0].label(), "Processor Fan");
+    assert_eq!(fans[0].component_type(), ComponentType::Fan);
+    assert_eq!(fans[0].rpm(), Some(2345.0));
+    assert!((fans[0].percentage().unwrap() - 47.857).abs() < 0.001);
+    assert!(provider
+        .thermal_components()
+        .iter()
+        .all(|c| c.component_type() != ComponentType::Fan));
+
+    let provider = fixture("ryzen-k10temp-desktop");
+    let fans = provider
+        .fan_components()
+        .into_iter()
+        .map(|c| (c.label(), c.fan().unwrap()))
+        .collect::<Vec<_>>();
+
+    let (_, fan) = fans.iter().find(|(l, _)| l == "nct6798 Fan 2").unwrap();
+    assert_eq!(fan.rpm(), Some(1182));
+    assert_eq!(fan.pwm(), Some(96));
+    assert_eq!(fan.mode(), Some(FanControlMode::Automatic(5)));
+    // Without a maximum speed, the percentage falls back to the PWM duty cycle.
+    assert!((fan.percentage().unwrap() - 37.647).abs() < 0.001);
+
+    let (_, fan) = fans.iter().find(|(l, _)| l == "amdgpu Fan 1").unwrap();
+    assert_eq!(fan.max_rpm(), Some(3300));
+    assert_eq!(fan.percentage(), Some(0.0));
+}
+
+#[test]
+fn thermal_framework() {
+    let root = scratch_root("thermal-framework", Some("rpi-thermal-zone"));
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+
+    let device = &provider.cooling_devices()[0];
+    assert_eq!(device.index(), 0);
+    assert_eq!(device.kind(), "cpufreq-cpu0");
+    assert_eq!((device.state(), device.max_state()), (0, 4));
+
+    let zone = &provider.thermal_zones()[0];
+    assert_eq!(zone.kind(), "cpu-thermal");
+    assert_eq!(zone.mode(), Some(ThermalZoneMode::Enabled));
+    assert_eq!(zone.policy(), Some("step_wise"));
+    assert_eq!(
+        zone.available_policies(),
+        ["power_allocator", "step_wise", "user_space"]
+    );
+    assert_eq!(
+        zone.trip_points(),
+        [
+            TripPoint {
+                index: 0,
+                kind: TripPointType::Critical,
+                temperature: Temperature::from_celsius(110.0),
+                hysteresis: Some(Temperature::default()),
+            },
+            TripPoint {
+                index: 1,
+                kind: TripPointType::Passive,
+                temperature: Temperature::from_celsius(80.0),
+                hysteresis: Some(Temperature::from_celsius(2.0)),
+            },
+        ]
+    );
+    assert_eq!(
+        zone.cooling_bindings(),
+        [CoolingBinding {
+            cooling_device: 0,
+            trip_point: Some(1),
+            weight: Some(1024),
+        }]
+    );
+
+    // Take over cooling from the governor.
+    let zone = &mut provider.thermal_zones_mut()[0];
+    assert!(matches!(
+        zone.set_policy("bang_bang"),
+        Err(LinuxError::InvalidData(_))
+    ));
+    zone.set_policy("user_space").unwrap();
+    zone.set_mode(ThermalZoneMode::Disabled).unwrap();
+
+    let device = &mut provider.cooling_devices_mut()[0];
+    assert!(matches!(
+        device.set_state(5),
+        Err(LinuxError::InvalidData(_))
+    ));
+    device.set_state(3).unwrap();
+
+    let zone = root.join("sys/devices/virtual/thermal/thermal_zone0");
+    let read = |path: PathBuf| std::fs::read_to_string(path).unwrap();
+    assert_eq!(read(zone.join("policy")), "user_space");
+    assert_eq!(read(zone.join("mode")), "disabled");
+    assert_eq!(
+        read(root.join("sys/devices/virtual/thermal/cooling_device0/cur_state")),
+        "3"
+    );
+
+    // States changed behind tmt's back are picked up on refresh.
+    std::fs::write(zone.join("policy"), "power_allocator\n").unwrap();
+    provider.refresh().unwrap();
+    assert_eq!(
+        provider.thermal_zones()[0].policy(),
+        Some("power_allocator")
+    );
+    assert_eq!(provider.cooling_devices()[0].state(), 3);
+
+    // A cooling device or zone that can't be read keeps its last known state, and doesn't keep
+    // the provider from starting.
+    let cur_state = root.join("sys/devices/virtual/thermal/cooling_device0/cur_state");
+    std::fs::remove_file(&cur_state).unwrap();
+    std::fs::create_dir(&cur_state).unwrap();
+    std::fs::write(zone.join("trip_point_1_type"), "lukewarm\n").unwrap();
+    provider.refresh().unwrap();
+    let device = &provider.cooling_devices()[0];
+    assert_eq!(device.state(), 3);
+    assert!(matches!(device.status(), ReadingStatus::Stale(_)));
+    let thermal_zone = &provider.thermal_zones()[0];
+    assert_eq!(thermal_zone.trip_points().len(), 2);
+    assert!(matches!(thermal_zone.status(), ReadingStatus::Stale(_)));
+
+    let provider = Provider::with_root(&root).unwrap();
+    let device = &provider.cooling_devices()[0];
+    assert!(matches!(device.status(), ReadingStatus::Unavailable(_)));
+    assert!(provider.thermal_zones()[0].trip_points().is_empty());
+
+    // So does a zone whose type and thresholds can't be read, which is read with the defaults.
+    for attribute in ["type", "trip_point_0_temp"] {
+        std::fs::remove_file(zone.join(attribute)).unwrap();
+        std::fs::create_dir(zone.join(attribute)).unwrap();
+    }
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+    let reading = &provider.thermal_components()[0].temperatures()[0];
+    assert_eq!(reading.label(), "thermal_zone0");
+    assert_eq!(reading.critical(), Temperature::from_celsius(100.0));
+
+    // Zones exposed through hwmon are still managed.
+    let laptop = fixture("intel-coretemp-laptop");
+    let zones = laptop
+        .thermal_zones()
+        .iter()
+        .map(|zone| zone.kind())
+        .collect::<Vec<_>>();
+    assert_eq!(zones, ["acpitz", "x86_pkg_temp", "iwlwifi_1"]);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn cpu_usage() {
+    let root = scratch_root("cpu-usage", Some("ryzen-k10temp-desktop"));
+    let stat = std::fs::read_to_string(root.join("proc/stat")).unwrap();
+
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+
+    // Nothing has happened since the provider was created.
+    let k10temp = |provider: &Provider| {
+        provider
+            .thermal_components()
+            .into_iter()
+            .find(|c| c.label() == "k10temp")
+            .map(|c| (c.percentage(), c.temperatures()))
+            .unwrap()
+    };
+    assert_eq!(k10temp(&provider).0, None);
+
+    // Keep the first CCD fully busy and the second idle for 100 ticks.
+    let stat = stat
+        .lines()
+        .map(|line| {
+            let (label, fields) = line.split_once(char::is_whitespace).unwrap();
+            let Some(cpu) = label.strip_prefix("cpu") else {
+                return line.to_string();
+            };
+
+            let mut fields = fields
+                .split_whitespace()
+                .map(|field| field.parse::<u64>().unwrap())
+                .collect::<Vec<_>>();
+            match cpu.parse::<u32>().ok() {
+                None => (fields[0], fields[3]) = (fields[0] + 1200, fields[3] + 1200),
+                Some(cpu) if cpu % 12 < 6 => fields[0] += 100,
+                Some(_) => fields[3] += 100,
+            }
+
+            let fields = fields.iter().map(u64::to_string).collect::<Vec<_>>();
+            format!("{} {}", label, fields.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(root.join("proc/stat"), stat).unwrap();
+    provider.refresh().unwrap();
+
+    assert_eq!(provider.usage().total(), Some(50.0));
+    assert_eq!(provider.usage().cpu(0), Some(100.0));
+    assert_eq!(provider.usage().cpu(23), Some(0.0));
+
+    let (percentage, readings) = k10temp(&provider);
+    assert_eq!(percentage, Some(50.0));
+    let usage = |label: &str| {
+        readings
+            .iter()
+            .find(|r| r.label() == label)
+            .unwrap()
+            .usage()
+    };
+    assert_eq!(usage("Tctl"), Some(50.0));
+    assert_eq!(usage("Tccd1"), Some(100.0));
+    assert_eq!(usage("Tccd2"), Some(0.0));
+
+    // Only CPUs report utilisation.
+    assert!(provider
+        .thermal_components()
+        .into_iter()
+        .filter(|c| c.component_type() != ComponentType::Cpu)
+        .all(|c| c.percentage().is_none()));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn colliding_labels() {
+    let labels = |provider: &Provider, component: &str| {
+        provider
+            .thermal_components()
+            .into_iter()
+            .find(|c| c.label() == component)
+            .unwrap()
+            .temperatures()
+            .iter()
+            .map(|r| (r.label(), r.temperature().celsius()))
+            .collect::<Vec<_>>()
+    };
+
+    // Unlabelled channels are named after the chip, so they all share a label.
+    let provider = fixture("intel-coretemp-laptop");
+    assert_eq!(
+        labels(&provider, "dell_smm"),
+        [
+            ("dell_smm 1".to_string(), 44.0),
+            ("dell_smm 2".to_string(), 35.0),
+            ("dell_smm 3".to_string(), 31.0),
+        ]
+    );
+
+    // Readings are ordered by channel index rather than by name.
+    let root = scratch_root("colliding-labels", Some("ryzen-k10temp-desktop"));
+    let k10temp = root.join("sys/devices/pci0000:00/0000:00:18.3/hwmon/hwmon2");
+    std::fs::write(k10temp.join("temp10_label"), "Tccd1\n").unwrap();
+    std::fs::write(k10temp.join("temp10_input"), "40000\n").unwrap();
+
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+    assert_eq!(
+        labels(&provider, "k10temp"),
+        [
+            ("Tctl".to_string(), 52.125),
+            ("Tccd1 3".to_string(), 48.75),
+            ("Tccd2".to_string(), 44.5),
+            ("Tccd1 10".to_string(), 40.0),
+        ]
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn failing_channels_are_isolated() {
+    let root = scratch_root("failing-channels", Some("ryzen-k10temp-desktop"));
+    let k10temp = root.join("sys/devices/pci0000:00/0000:00:18.3/hwmon/hwmon2");
+    let amdgpu = root.join(
+        "sys/devices/pci0000:00/0000:00:03.1/0000:0b:00.0/0000:0c:00.0/0000:0d:00.0/hwmon/hwmon3",
+    );
+
+    // A channel that has never been read successfully has no value.
+    std::fs::write(k10temp.join("temp4_input"), "garbage\n").unwrap();
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider
+        .refresh()
+        .expect("a failing channel should not fail the refresh");
+
+    let status = |provider: &Provider, label: &str| {
+        provider
+            .thermal_components()
+            .into_iter()
+            .filter(|c| c.label() == "k10temp")
+            .flat_map(|c| c.temperatures())
+            .find(|r| r.label() == label)
+            .unwrap()
+            .status()
+    };
+    assert!(matches!(
+        status(&provider, "Tccd2"),
+        ReadingStatus::Unavailable(err) if err.contains("garbage")
+    ));
+    assert!((reading(&provider, "k10temp", "Tccd2") - 0.0).abs() < f64::EPSILON);
+    assert_eq!(status(&provider, "Tccd1"), ReadingStatus::Ok);
+
+    // A channel that stops reading keeps its last value, while the others keep updating.
+    std::fs::write(k10temp.join("temp4_input"), "51000\n").unwrap();
+    provider.refresh().unwrap();
+    assert_eq!(status(&provider, "Tccd2"), ReadingStatus::Ok);
+
+    std::fs::remove_file(k10temp.join("temp3_input")).unwrap();
+    std::fs::create_dir(k10temp.join("temp3_input")).unwrap();
+    std::fs::write(k10temp.join("temp4_input"), "55000\n").unwrap();
+    std::fs::remove_file(amdgpu.join("in0_input")).unwrap();
+    std::fs::create_dir(amdgpu.join("in0_input")).unwrap();
+    provider.refresh().unwrap();
+
+    let tccd1 = status(&provider, "Tccd1");
+    assert!(matches!(tccd1, ReadingStatus::Stale(_)), "{:?}", tccd1);
+    assert!(tccd1.error().is_some());
+    assert!((reading(&provider, "k10temp", "Tccd1") - 48.75).abs() < 1e-9);
+    assert!((reading(&provider, "k10temp", "Tccd2") - 55.0).abs() < 1e-9);
+
+    let gpu = provider
+        .sensor_components()
+        .into_iter()
+        .find(|c| c.label() == "amdgpu")
+        .unwrap()
+        .sensors();
+    assert!(matches!(gpu[0].status, ReadingStatus::Stale(_)));
+    assert!((gpu[0].value - 0.737).abs() < 1e-9);
+    assert_eq!(gpu[1].status, ReadingStatus::Ok);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn hotplugged_sensors() {
+    let root = scratch_root("hotplug", Some("ryzen-k10temp-desktop"));
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.set_rescan_interval(None);
+    provider.refresh().unwrap();
+
+    let labels = |provider: &Provider| {
+        let mut labels = provider
+            .thermal_components()
+            .into_iter()
+            .map(|c| c.label())
+            .collect::<Vec<_>>();
+        labels.sort();
+        labels
+    };
+    assert_eq!(labels(&provider), ["amdgpu", "k10temp", "nct6798", "nvme"]);
+
+    // Take over a fan, which has to survive rescans without being restored.
+    let enable = root.join("sys/devices/platform/nct6775.656/hwmon/hwmon1/pwm1_enable");
+    let original = std::fs::read_to_string(&enable).unwrap();
+    provider
+        .fan_components_mut()
+        .into_iter()
+        .find_map(|c| c.fan_mut().filter(|fan| fan.index() == 1))
+        .unwrap()
+        .set_mode(FanControlMode::FullSpeed)
+        .unwrap();
+
+    // Components whose device disappeared, such as an unplugged eGPU, trigger a rescan on their
+    // own.
+    let devices = root.join("sys/devices/pci0000:00");
+    std::fs::remove_dir_all(devices.join("0000:00:03.1")).unwrap();
+    provider.refresh().unwrap();
+    assert_eq!(labels(&provider), ["k10temp", "nct6798", "nvme"]);
+
+    // An NVMe drive is hot-swapped in, reusing the eGPU's hwmon index.
+    let nvme = devices.join("0000:00:01.2/0000:02:00.0/nvme/nvme1/hwmon/hwmon3");
+    std::fs::create_dir_all(&nvme).unwrap();
+    std::fs::write(nvme.join("name"), "nvme\n").unwrap();
+    std::fs::write(nvme.join("temp1_input"), "38850\n").unwrap();
+    std::fs::write(nvme.join("temp1_label"), "Sensor 1\n").unwrap();
+    let class = root.join("sys/class/hwmon/hwmon3");
+    std::fs::remove_file(&class).unwrap();
+    std::os::unix::fs::symlink(
+        "../../devices/pci0000:00/0000:00:01.2/0000:02:00.0/nvme/nvme1/hwmon/hwmon3",
+        &class,
+    )
+    .unwrap();
+
+    assert!(provider.rescan().unwrap());
+    provider.refresh().unwrap();
+    assert_eq!(labels(&provider), ["k10temp", "nct6798", "nvme", "nvme"]);
+    assert!((reading(&provider, "nvme", "Sensor 1") - 38.85).abs() < 1e-9);
+    assert!(!provider.rescan().unwrap());
+
+    let fan = provider
+        .fan_components()
+        .into_iter()
+        .find_map(|c| c.fan().filter(|fan| fan.index() == 1))
+        .unwrap();
+    assert!(fan.is_overridden());
+    assert_eq!(std::fs::read_to_string(&enable).unwrap().trim(), "0");
+
+    // New devices are picked up by the periodic rescan.
+    std::fs::write(
+        root.join("sys/devices/pci0000:00/0000:00:18.3/hwmon/hwmon2/temp5_input"),
+        "60000\n",
+    )
+    .unwrap();
+    let usb = root.join("sys/class/hwmon/hwmon7");
+    std::fs::create_dir_all(&usb).unwrap();
+    std::fs::write(usb.join("name"), "ucsi_source_psy_USBC000:001\n").unwrap();
+    std::fs::write(usb.join("in0_input"), "20000\n").unwrap();
+    provider.refresh().unwrap();
+    assert!(provider
+        .sensor_components()
+        .iter()
+        .all(|c| !c.label().starts_with("ucsi")));
+
+    provider.set_rescan_interval(Some(std::time::Duration::ZERO));
+    provider.refresh().unwrap();
+    assert!(provider
+        .sensor_components()
+        .iter()
+        .any(|c| c.label().starts_with("ucsi")));
+
+    // A scan that fails, e.g. while a device is being hotplugged, keeps every component as it
+    // was, along with the fan that was taken over.
+    let k10temp = provider
+        .thermal_components()
+        .into_iter()
+        .find(|c| c.label() == "k10temp")
+        .unwrap()
+        .id();
+    let class = root.join("sys/class/hwmon");
+    std::fs::rename(&class, root.join("sys/class/hwmon.bak")).unwrap();
+    std::fs::write(&class, "").unwrap();
+    let err = provider.rescan().unwrap_err();
+    let io_error = err.source().and_then(std::error::Error::source).unwrap();
+    assert!(io_error.is::<std::io::Error>());
+    assert!(provider.refresh().is_err());
+    assert_eq!(labels(&provider), ["k10temp", "nct6798", "nvme", "nvme"]);
+    assert_eq!(
+        provider.component_by_id(&k10temp).unwrap().label(),
+        "k10temp"
+    );
+    assert_eq!(std::fs::read_to_string(&enable).unwrap().trim(), "0");
+    std::fs::remove_file(&class).unwrap();
+    std::fs::rename(root.join("sys/class/hwmon.bak"), &class).unwrap();
+
+    drop(provider);
+    assert_eq!(std::fs::read_to_string(&enable).unwrap(), original.trim());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn fan_control_restores_original_mode() {
+    // A single nct6798 fan.
+    let root = scratch_root("fan-control", None);
+    let hwmon = root.join("sys/class/hwmon/hwmon1");
+    std::fs::create_dir_all(&hwmon).unwrap();
+    for (attribute, value) in [
+        ("name", "nct6798"),
+        ("fan1_input", "1182"),
+        ("pwm1", "96"),
+        ("pwm1_enable", "5"),
+    ] {
+        std::fs::write(hwmon.join(attribute), format!("{}\n", value)).unwrap();
+    }
+    let attribute = |name: &str| {
+        std::fs::read_to_string(root.join("sys/class/hwmon/hwmon1").join(name))
+            .unwrap()
+            .trim()
+            .to_string()
+    };
+
+    let mut provider = Provider::with_root(&root).unwrap();
+    let fan = provider
+        .fan_components_mut()
+        .into_iter()
+        .next()
+        .and_then(|c| c.fan_mut())
+        .unwrap();
+
+    assert!(matches!(fan.set_pwm(10), Err(LinuxError::UnsafeFanSpeed)));
+    assert!(matches!(
+        fan.set_duty_cycle(100.5),
+        Err(LinuxError::UnsafeFanSpeed)
+    ));
+    assert_eq!(attribute("pwm1_enable"), "5");
+
+    fan.set_duty_cycle(80.0).unwrap();
+    assert!(fan.is_overridden());
+    assert_eq!(attribute("pwm1_enable"), "1");
+    assert_eq!(attribute("pwm1"), "204");
+
+    fan.restore().unwrap();
+    assert!(!fan.is_overridden());
+    assert_eq!(attribute("pwm1_enable"), "5");
+
+    // Dropping the provider restores fans that are still overridden.
+    fan.set_mode(FanControlMode::FullSpeed).unwrap();
+    assert_eq!(attribute("pwm1_enable"), "0");
+    drop(provider);
+    assert_eq!(attribute("pwm1_enable"), "5");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn sensors_config() {
+    let root = scratch_root("sensors-config", Some("ryzen-k10temp-desktop"));
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+
+    // Labels from /etc/sensors3.conf are overridden by the board's file in /etc/sensors.d, and
+    // the ignored AUXTIN0 channel is gone.
+    let labels = provider
+        .thermal_components()
+        .into_iter()
+        .filter(|c| c.label() == "nct6798")
+        .flat_map(|c| c.temperatures())
+        .map(|r| r.label().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(labels, ["SYSTIN", "CPU Socket", "Chipset"]);
+    assert_eq!(reading(&provider, "nct6798", "CPU Socket"), 41.5);
+    assert_eq!(reading(&provider, "nct6798", "Chipset"), 32.0);
+
+    let board = provider
+        .sensor_components()
+        .into_iter()
+        .find(|c| c.label() == "nct6798")
+        .unwrap()
+        .sensors();
+    assert_eq!(board[0].label, "Vcore");
+    assert_eq!(board[1].label, "+5V");
+    assert!((board[1].value - 5.08).abs() < 1e-9);
+
+    // `set` statements are only written when asked to, converted back through `compute`.
+    let chip = root.join("sys/devices/platform/nct6775.656/hwmon/hwmon1");
+    let read = |attribute: &str| std::fs::read_to_string(chip.join(attribute)).unwrap();
+    assert_eq!(read("in1_min").trim(), "0");
+    provider.apply_sensors_config_limits().unwrap();
+    assert_eq!(read("in1_min"), "950");
+    assert_eq!(read("in1_max"), "1050");
+
+    // Ignored fans are dropped when the configuration is replaced.
+    let config = SensorsConfig::parse("chip \"nct6798-isa-*\"\n    ignore fan1\n").unwrap();
+    let chip_name = ChipName {
+        prefix: "nct6798".to_string(),
+        bus: BusType::Isa,
+        bus_number: 0,
+        address: 0x290,
+        adapter: None,
+    };
+    assert!(config.chip(&chip_name).is_ignored("fan1"));
+    assert_eq!(config.chip(&chip_name).compute("in1", 1.016), 1.016);
+    provider.set_sensors_config(config);
+    provider.refresh().unwrap();
+    let fans = provider
+        .fan_components()
+        .into_iter()
+        .map(|c| c.label())
+        .collect::<Vec<_>>();
+    assert_eq!(fans, ["nct6798 Fan 2", "amdgpu Fan 1"]);
+    assert_eq!(reading(&provider, "nct6798", "AUXTIN0"), 127.0);
+
+    // Buses are matched by adapter name, whatever number the adapter was given at boot.
+    let xeon = fixture("xeon-dual-socket");
+    assert_eq!(reading(&xeon, "jc42", "DIMM A1"), 81.25);
+
+    let error =
+        SensorsConfig::parse("chip \"k10temp-*\"\n    label temp1 \"Tctl\"\n    lable temp2\n")
+            .unwrap_err();
+    assert_eq!(error.line, 3);
+    assert_eq!(
+        SensorsConfig::parse("label temp1 \"CPU\"\n")
+            .unwrap_err()
+            .to_string(),
+        "line 1: label statement outside of a chip"
+    );
+
+    // Drives are named after their SCSI host, and a file that doesn't parse is skipped rather
+    // than failing the provider.
+    let drive = root.join("sys/devices/pci0000:00/0000:00:17.0/ata1/host2/target2:0:0/2:0:0:0");
+    std::fs::create_dir_all(drive.join("hwmon/hwmon9")).unwrap();
+    std::fs::write(drive.join("hwmon/hwmon9/name"), "drivetemp\n").unwrap();
+    std::fs::write(drive.join("hwmon/hwmon9/temp1_input"), "36000\n").unwrap();
+    std::os::unix::fs::symlink("../..", drive.join("hwmon/hwmon9/device")).unwrap();
+    std::os::unix::fs::symlink(
+        "../../devices/pci0000:00/0000:00:17.0/ata1/host2/target2:0:0/2:0:0:0/hwmon/hwmon9",
+        root.join("sys/class/hwmon/hwmon9"),
+    )
+    .unwrap();
+    let sensors_d = root.join("etc/sensors.d");
+    std::fs::write(
+        sensors_d.join("drives.conf"),
+        "chip \"drivetemp-scsi-*\"\n    label temp1 \"Drive Bay 1\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        sensors_d.join("fans.conf"),
+        "chip \"nct6798-*\"\n    lable fan1\n",
+    )
+    .unwrap();
+
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+    assert_eq!(reading(&provider, "drivetemp", "Drive Bay 1"), 36.0);
+    assert_eq!(reading(&provider, "nct6798", "CPU Socket"), 41.5);
+    let errors = provider.sensors_config().errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].ends_with("fans.conf: line 2: unknown statement lable"));
+
+    let drive = ChipName {
+        prefix: "drivetemp".to_string(),
+        bus: BusType::Scsi,
+        bus_number: 2,
+        address: 0,
+        adapter: None,
+    };
+    assert_eq!(drive.to_string(), "drivetemp-scsi-2-0");
+    let config = SensorsConfig::parse(
+        "chip \"drivetemp-scsi-1-*\" \"acpitz-acpi-0\"\n    label temp1 \"Ambient\"\n",
+    )
+    .unwrap();
+    assert!(config.chip(&drive).is_empty());
+    let acpitz = ChipName {
+        prefix: "acpitz".to_string(),
+        bus: BusType::Acpi,
+        ..drive
+    };
+    assert_eq!(acpitz.to_string(), "acpitz-acpi-0");
+    assert_eq!(config.chip(&acpitz).label("temp1"), Some("Ambient"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn runtime_suspended_devices() {
+    let root = scratch_root("runtime-suspended", Some("ryzen-k10temp-desktop"));
+    let bridge = root.join("sys/devices/pci0000:00/0000:00:03.1");
+    let gpu = bridge.join("0000:0b:00.0/0000:0c:00.0/0000:0d:00.0");
+    let hwmon = gpu.join("hwmon/hwmon3");
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+
+    let junction = |provider: &Provider| {
+        provider
+            .thermal_components()
+            .into_iter()
+            .filter(|c| c.label() == "amdgpu")
+            .flat_map(|c| c.temperatures())
+            .find(|r| r.label() == "junction")
+            .unwrap()
+    };
+    let gpu_fan = |provider: &Provider| {
+        provider
+            .fan_components()
+            .into_iter()
+            .find(|c| c.label() == "amdgpu Fan 1")
+            .map(|c| (c.fan().unwrap().is_sleeping(), c.fan().unwrap().rpm()))
+            .unwrap()
+    };
+    assert_eq!(gpu_fan(&provider), (false, Some(0)));
+
+    // A suspended GPU isn't read, and keeps its last known values.
+    std::fs::write(gpu.join("power/runtime_status"), "suspended\n").unwrap();
+    std::fs::write(hwmon.join("temp2_input"), "60000\n").unwrap();
+    std::fs::write(hwmon.join("fan1_input"), "1200\n").unwrap();
+    provider.refresh().unwrap();
+
+    let reading = junction(&provider);
+    assert_eq!(reading.status(), ReadingStatus::Sleeping);
+    assert!(reading.status().error().is_none());
+    assert_eq!(reading.temperature().celsius(), 47.0);
+    assert_eq!(gpu_fan(&provider), (true, Some(0)));
+    assert!(provider
+        .sensor_components()
+        .into_iter()
+        .find(|c| c.label() == "amdgpu")
+        .unwrap()
+        .sensors()
+        .iter()
+        .all(|s| s.status == ReadingStatus::Sleeping));
+
+    // So is one behind a suspended PCIe port.
+    std::fs::write(gpu.join("power/runtime_status"), "active\n").unwrap();
+    std::fs::write(bridge.join("power/runtime_status"), "suspended\n").unwrap();
+    provider.refresh().unwrap();
+    assert_eq!(junction(&provider).status(), ReadingStatus::Sleeping);
+
+    // Once it wakes up, it is read again.
+    std::fs::write(bridge.join("power/runtime_status"), "active\n").unwrap();
+    provider.refresh().unwrap();
+    let reading = junction(&provider);
+    assert_eq!(reading.status(), ReadingStatus::Ok);
+    assert_eq!(reading.temperature().celsius(), 60.0);
+    assert_eq!(gpu_fan(&provider), (false, Some(1200)));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn nvidia_smi_gpus() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = scratch_root("nvidia-smi", Some("nvidia-cuda-workstation"));
+    let stub = root.join("usr/bin/nvidia-smi");
+
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.set_nvidia_smi(&stub);
+    assert_eq!(provider.gpu_name(), None);
+    provider.refresh().unwrap();
+
+    assert_eq!(provider.gpu_name().as_deref(), Some("NVIDIA RTX A6000"));
+    let gpus = provider.thermal_components_by_type(ComponentType::Gpu);
+    let labels = gpus.iter().map(|c| c.label()).collect::<Vec<_>>();
+    assert_eq!(labels, ["NVIDIA RTX A6000", "NVIDIA GeForce RTX 4090"]);
+
+    // The A6000 doesn't report its memory temperature.
+    let a6000 = gpus[0].temperatures();
+    assert_eq!(a6000.len(), 1);
+    assert_eq!(a6000[0].label(), "GPU");
+    assert_eq!(a6000[0].temperature().celsius(), 64.0);
+    assert_eq!(a6000[0].usage(), Some(87.0));
+    assert!(a6000[0].alarms().is_empty());
+
+    // The 4090 is thermally throttling.
+    let rtx4090 = gpus[1].nvidia().unwrap();
+    assert_eq!(rtx4090.bus_id(), "0000:41:00.0");
+    assert_eq!(rtx4090.fan_speed(), Some(100.0));
+    assert_eq!(
+        rtx4090.throttle_reasons(),
+        ThrottleReasons::SW_THERMAL_SLOWDOWN | ThrottleReasons::HW_THERMAL_SLOWDOWN
+    );
+    let id = SensorId::new("nvidia-smi:nvidia@pci0000:40/0000:40:01.1/0000:41:00.0#gpu");
+    let core = provider.temperature_by_id(&id).unwrap();
+    assert_eq!(core.temperature().celsius(), 83.0);
+    assert_eq!(core.alarms(), TemperatureAlarms::MAX);
+    assert_eq!(
+        reading(&provider, "NVIDIA GeForce RTX 4090", "Memory"),
+        92.0
+    );
+
+    let power = gpus[1].sensors();
+    assert_eq!(power[0].kind, SensorKind::Power);
+    assert!((power[0].value - 431.9).abs() < 1e-9);
+    assert_eq!(power[0].max, Some(450.0));
+
+    // nvidia-smi isn't even run while every GPU is asleep.
+    let gpu = |bus: &str, device: &str| root.join(format!("sys/devices/{}/{}", bus, device));
+    let devices = [
+        gpu("pci0000:00/0000:00:01.1", "0000:01:00.0"),
+        gpu("pci0000:40/0000:40:01.1", "0000:41:00.0"),
+    ];
+    let suspend = |status: &str| {
+        for device in &devices {
+            std::fs::write(device.join("power/runtime_status"), status).unwrap();
+        }
+    };
+    let status = |provider: &Provider| {
+        provider.thermal_components_by_type(ComponentType::Gpu)[0].temperatures()[0].status()
+    };
+
+    std::fs::remove_file(&stub).unwrap();
+    suspend("suspended\n");
+    provider.refresh().unwrap();
+    assert_eq!(status(&provider), ReadingStatus::Sleeping);
+
+    // A missing or failing nvidia-smi leaves the GPUs stale, without failing the refresh.
+    suspend("active\n");
+    provider.refresh().unwrap();
+    let error = status(&provider);
+    assert!(
+        matches!(&error, ReadingStatus::Stale(err) if err.contains("failed to run nvidia-smi")),
+        "{:?}",
+        error
+    );
+
+    std::fs::write(
+        &stub,
+        "#!/bin/sh\necho \"NVIDIA-SMI has failed because it couldn't communicate with the NVIDIA \
+         driver.\"\nexit 9\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+    provider.refresh().unwrap();
+    let error = status(&provider);
+    assert!(
+        matches!(&error, ReadingStatus::Stale(err) if err.contains("couldn't communicate")),
+        "{:?}",
+        error
+    );
+    assert_eq!(reading(&provider, "NVIDIA RTX A6000", "GPU"), 64.0);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn typed_errors() {
+    let root = scratch_root("typed-errors", Some("ryzen-k10temp-desktop"));
+    let mut provider = Provider::with_root(&root).unwrap();
+    provider.refresh().unwrap();
+
+    // A fan whose speed can no longer be read keeps its last speed, marked as stale, rather than
+    // failing the refresh.
+    let fan = |provider: &Provider| {
+        let fan = provider
+            .fan_components()
+            .into_iter()
+            .find(|c| c.label() == "nct6798 Fan 2")
+            .unwrap()
+            .fan()
+            .unwrap();
+        (fan.rpm(), fan.status())
+    };
+    assert_eq!(fan(&provider), (Some(1182), ReadingStatus::Ok));
+    std::fs::remove_file(root.join("sys/devices/platform/nct6775.656/hwmon/hwmon1/fan2_input"))
+        .unwrap();
+    provider.refresh().unwrap();
+    let (rpm, status) = fan(&provider);
+    assert_eq!(rpm, Some(1182));
+    assert!(matches!(status, ReadingStatus::Stale(_)));
+
+    // A chip that went away fails to reset its history, naming the chip.
+    let chip = provider
+        .thermal_components()
+        .into_iter()
+        .find(|c| c.label() == "k10temp")
+        .unwrap()
+        .id();
+    std::fs::remove_dir_all(root.join("sys/devices/pci0000:00/0000:00:18.3/hwmon/hwmon2")).unwrap();
+
+    let err = provider.reset_history().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert_eq!(err.component(), Some(&chip));
+    assert!(err.to_string().starts_with(chip.as_str()));
+    let source = std::error::Error::source(&err).unwrap();
+    assert!(matches!(
+        source.downcast_ref::<LinuxError>(),
+        Some(LinuxError::IoError(_))
+    ));
+
+    // Devices that went away fail with ENODEV rather than ENOENT.
+    let gone = Error::from(LinuxError::IoError(std::io::Error::from_raw_os_error(19)));
+    assert_eq!(gone.kind(), ErrorKind::NotFound);
+    assert_eq!(gone.component(), None);
+    let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+    assert_eq!(
+        Error::from(LinuxError::from(denied)).kind(),
+        ErrorKind::PermissionDenied
+    );
+    assert_eq!(
+        Error::from(LinuxError::UnsafeFanSpeed).kind(),
+        ErrorKind::Unsupported
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn snapshot() {
+    let provider = fixture("ryzen-k10temp-desktop");
+    let before = std::time::SystemTime::now();
+    let snapshot = provider.snapshot();
+    assert!(snapshot.captured_at >= before);
+
+    assert_eq!(snapshot.system.cpu_name, provider.cpu_name());
+    assert_eq!(
+        snapshot.system.board_name.as_deref(),
+        Some("ROG STRIX X570-E GAMING")
+    );
+    assert_eq!(snapshot.system.thread_count, Some(24));
+
+    // Every component is captured once, even if it is both a thermal and a sensor component.
+    let mut ids = provider
+        .thermal_components()
+        .into_iter()
+        .chain(provider.fan_components())
+        .chain(provider.sensor_components())
+        .map(Component::id)
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(snapshot.components.len(), ids.len());
+
+    let k10temp = provider
+        .thermal_components()
+        .into_iter()
+        .find(|c| c.label() == "k10temp")
+        .unwrap();
+    let tctl = k10temp
+        .temperatures()
+        .into_iter()
+        .find(|r| r.label() == "Tctl")
+        .unwrap();
+    let captured = snapshot.temperature_by_id(&tctl.id()).unwrap();
+    assert_eq!(captured.temperature, Temperature::from_celsius(52.125));
+    assert_eq!(captured.critical, tctl.critical());
+    assert_eq!(captured.status, ReadingStatus::Ok);
+    assert_eq!(
+        snapshot.component_by_id(&tctl.id()).unwrap().component_type,
+        ComponentType::Cpu
+    );
+
+    let fan = snapshot
+        .components
+        .iter()
+        .find(|c| c.component_type == ComponentType::Fan)
+        .unwrap();
+    assert!(fan.rpm.is_some());
+    assert!(fan.temperatures.is_empty());
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_value(&snapshot).unwrap();
+        let component = json["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["label"] == "k10temp")
+            .unwrap();
+        assert_eq!(component["component_type"], "Cpu");
+        let reading = &component["temperatures"][0];
+        assert_eq!(reading["id"], tctl.id().as_str());
+        assert_eq!(reading["temperature"], 52.125);
+        assert!(reading["alarms"].as_array().unwrap().is_empty());
+
+        let round_trip: tmt_core::Snapshot = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, snapshot);
+    }
+}
@@ -0,0 +1,234 @@
+//! Exercises the mock provider against the scenarios in `scenarios`.
+
+#![cfg(feature = "mock")]
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tmt_core::mock::{MockComponents, Scenario, Signal};
+use tmt_core::{
+    BatteryStatus, Component, ComponentType, ErrorKind, Interface, SensorKind, Temperature,
+    TemperatureAlarms, TemperatureReading,
+};
+
+fn scenario_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("scenarios")
+        .join(name)
+}
+
+fn secs(secs: f64) -> Duration {
+    Duration::from_secs_f64(secs)
+}
+
+#[test]
+fn signals() {
+    let ramp = Signal::Ramp {
+        from: 40.0,
+        to: 100.0,
+        duration: 60.0,
+        repeat: false,
+    };
+    assert_eq!(ramp.value_at(secs(0.0)), 40.0);
+    assert_eq!(ramp.value_at(secs(30.0)), 70.0);
+    assert_eq!(ramp.value_at(secs(90.0)), 100.0);
+
+    let sawtooth = Signal::Ramp {
+        from: 0.0,
+        to: 10.0,
+        duration: 10.0,
+        repeat: true,
+    };
+    assert_eq!(sawtooth.value_at(secs(12.5)), 2.5);
+
+    let sine = Signal::Sine {
+        mean: 50.0,
+        amplitude: 10.0,
+        period: 40.0,
+    };
+    assert_eq!(sine.value_at(secs(0.0)), 50.0);
+    assert!((sine.value_at(secs(10.0)) - 60.0).abs() < 1e-9);
+    assert!((sine.value_at(secs(30.0)) - 40.0).abs() < 1e-9);
+
+    let step = Signal::Step {
+        from: 1.0,
+        to: 2.0,
+        at: 5.0,
+    };
+    assert_eq!(step.value_at(secs(4.9)), 1.0);
+    assert_eq!(step.value_at(secs(5.0)), 2.0);
+
+    // Noise stays within its amplitude, and is the same for the same seed and time.
+    let noise = |seed| Signal::Noise {
+        mean: 50.0,
+        amplitude: 5.0,
+        seed,
+    };
+    let values = (0..100)
+        .map(|i| noise(7).value_at(secs(f64::from(i))))
+        .collect::<Vec<_>>();
+    assert!(values.iter().all(|v| (45.0..=55.0).contains(v)));
+    assert!(values.iter().any(|v| (v - values[0]).abs() > 0.1));
+    assert_eq!(noise(7).value_at(secs(3.0)), values[3]);
+    assert_ne!(noise(8).value_at(secs(3.0)), values[3]);
+}
+
+#[test]
+fn overheat_scenario() {
+    let mut provider = MockComponents::load(scenario_path("overheat.toml")).unwrap();
+    assert_eq!(provider.device_model_name(), "Mock Laptop");
+    assert_eq!(provider.gpu_name(), None);
+
+    let package = |provider: &MockComponents| {
+        provider.thermal_components_by_type(ComponentType::Cpu)[0].temperatures()[0].clone()
+    };
+    let reading = package(&provider);
+    assert_eq!(reading.id().as_str(), "mock:CPU#Package");
+    assert_eq!(reading.temperature(), Temperature::from_celsius(45.0));
+    assert_eq!(reading.high(), Temperature::from_celsius(85.0));
+    assert!(reading.alarms().is_empty());
+
+    provider.refresh_at(secs(40.0));
+    let reading = package(&provider);
+    assert!((reading.temperature().celsius() - 88.333).abs() < 0.001);
+    assert_eq!(reading.alarms(), TemperatureAlarms::MAX);
+
+    provider.refresh_at(secs(60.0));
+    let reading = package(&provider);
+    assert_eq!(reading.temperature(), Temperature::from_celsius(110.0));
+    assert_eq!(
+        reading.alarms(),
+        TemperatureAlarms::MAX | TemperatureAlarms::CRITICAL
+    );
+
+    let fan = provider.fan_components()[0];
+    assert_eq!(fan.rpm(), Some(0.0));
+    assert_eq!(fan.percentage(), Some(0.0));
+
+    let battery = provider.battery_components()[0];
+    let state = battery.battery().unwrap();
+    assert_eq!(state.status, BatteryStatus::Discharging);
+    assert_eq!(battery.percentage(), Some(54.5));
+    assert!(state.time_to_empty.is_some());
+
+    // The recorded maximum and minimum follow the signal until they are reset.
+    provider.refresh_at(secs(30.0));
+    let reading = package(&provider);
+    assert_eq!(reading.max(), Temperature::from_celsius(110.0));
+    assert_eq!(reading.lowest(), Some(Temperature::from_celsius(45.0)));
+
+    provider.reset_history().unwrap();
+    let reading = package(&provider);
+    assert_eq!(reading.max(), reading.temperature());
+    assert_eq!(reading.lowest(), Some(reading.temperature()));
+
+    // Nonsensical battery values leave no time to estimate rather than panicking.
+    let mut scenario = Scenario::load(scenario_path("overheat.toml")).unwrap();
+    let battery = scenario.components[2].battery.as_mut().unwrap();
+    battery.energy_full = Some(-52.0);
+    let provider = MockComponents::new(scenario);
+    let state = provider.battery_components()[0].battery().unwrap();
+    assert_eq!(state.time_to_empty, None);
+}
+
+#[test]
+fn demo_scenario() {
+    let mut provider = MockComponents::default();
+    provider.refresh().unwrap();
+
+    assert_eq!(provider.gpu_name().as_deref(), Some("Mock GPU"));
+    assert_eq!(provider.thread_count(), Some(4));
+    assert_eq!(provider.thermal_components().len(), 3);
+    assert_eq!(provider.fan_components().len(), 1);
+    assert!(provider.battery_components().is_empty());
+
+    let cpu = provider.thermal_components_by_type(ComponentType::Cpu)[0];
+    assert!(cpu.temperatures()[1].usage().is_some());
+    let sensors = provider.sensor_components();
+    assert_eq!(sensors.len(), 1);
+    assert_eq!(sensors[0].sensors()[0].kind, SensorKind::Voltage);
+
+    let snapshot = provider.snapshot();
+    assert_eq!(snapshot.components.len(), 4);
+}
+
+#[test]
+fn sensor_only_components() {
+    let scenario = r#"
+        [[components]]
+        label = "PSU"
+        type = "Sensor"
+
+        [[components.sensors]]
+        label = "12V"
+        kind = "Voltage"
+        signal = { kind = "constant", value = 12.1 }
+    "#;
+    let provider = MockComponents::new(Scenario::parse(scenario, Path::new("")).unwrap());
+
+    assert!(provider.thermal_components().is_empty());
+    let sensors = provider.sensor_components();
+    assert_eq!(sensors.len(), 1);
+    assert_eq!(sensors[0].sensors()[0].value, 12.1);
+    let id = sensors[0].sensors()[0].id.clone();
+    assert_eq!(provider.component_by_id(&id).unwrap().label(), "PSU");
+    assert_eq!(provider.snapshot().components.len(), 1);
+
+    // Labels can't contain the separator between a component and its channels.
+    let err = Scenario::parse(&scenario.replace("12V", "Rail #1"), Path::new("")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err.to_string().contains("Rail #1"));
+}
+
+#[test]
+fn file_signals() {
+    let root = std::env::temp_dir().join(format!("tmt-mock-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("traces")).unwrap();
+    std::fs::write(root.join("traces/cpu.txt"), "# captured\n40\n\n60.5\n80\n").unwrap();
+    std::fs::write(
+        root.join("replay.toml"),
+        r#"
+            [[components]]
+            label = "CPU"
+            type = "Cpu"
+
+            [[components.temperatures]]
+            label = "Package"
+            signal = { kind = "file", path = "traces/cpu.txt", interval = 2 }
+        "#,
+    )
+    .unwrap();
+
+    // Paths are relative to the scenario, and the values loop.
+    let mut provider = MockComponents::load(root.join("replay.toml")).unwrap();
+    let temperature = |provider: &MockComponents| {
+        provider.thermal_components()[0].temperatures()[0]
+            .temperature()
+            .celsius()
+    };
+    assert_eq!(temperature(&provider), 40.0);
+    provider.refresh_at(secs(3.0));
+    assert_eq!(temperature(&provider), 60.5);
+    provider.refresh_at(secs(6.5));
+    assert_eq!(temperature(&provider), 40.0);
+
+    std::fs::write(root.join("traces/cpu.txt"), "40\nhot\n").unwrap();
+    let err = Scenario::load(root.join("replay.toml")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err.to_string().contains("line 2"));
+
+    std::fs::remove_file(root.join("traces/cpu.txt")).unwrap();
+    let err = Scenario::load(root.join("replay.toml")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    // Typos are caught rather than silently ignored.
+    let err = Scenario::parse(
+        "[[components]]\nlabel = \"CPU\"\ntype = \"Cpu\"\n\
+         rmp = { kind = \"constant\", value = 1 }\n",
+        &root,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err.to_string().contains("rmp"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
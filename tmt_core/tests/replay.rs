@@ -0,0 +1,157 @@
+//! Records sessions of the mock provider and plays them back.
+
+#![cfg(all(feature = "replay", feature = "mock"))]
+
+use std::path::PathBuf;
+use std::time::Duration;
+use tmt_core::mock::MockComponents;
+use tmt_core::replay::{Playback, Recorder, ReplayComponents};
+use tmt_core::{
+    Component, ComponentSnapshot, ComponentType, ErrorKind, Interface, ReadingStatus, SensorId,
+    SensorKind, SensorReading, Snapshot, Temperature, TemperatureReading,
+};
+
+fn overheat() -> MockComponents {
+    MockComponents::load(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("scenarios/overheat.toml"))
+        .unwrap()
+}
+
+/// Records the overheat scenario every ten seconds for a minute.
+fn record() -> (Vec<u8>, Vec<Snapshot>) {
+    let mut provider = overheat();
+    let mut recorder = Recorder::new(Vec::new());
+    let start = std::time::SystemTime::now();
+
+    let snapshots = (0..=6)
+        .map(|i| {
+            let elapsed = Duration::from_secs(i * 10);
+            provider.refresh_at(elapsed);
+            let mut snapshot = provider.snapshot();
+            snapshot.captured_at = start + elapsed;
+            recorder.record(&snapshot).unwrap();
+            snapshot
+        })
+        .collect();
+
+    (recorder.into_inner(), snapshots)
+}
+
+fn package(provider: &impl Interface) -> Temperature {
+    provider.thermal_components()[0].temperatures()[0].temperature()
+}
+
+#[test]
+fn record_and_replay() {
+    let (recording, snapshots) = record();
+    let lines = std::str::from_utf8(&recording)
+        .unwrap()
+        .lines()
+        .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 8);
+    assert!(lines[0].contains("\"tmtrec\""));
+
+    // Components that didn't change are left out, e.g. the fan until it stops.
+    assert!(lines[1].contains("CPU Fan"));
+    assert!(!lines[2].contains("CPU Fan"));
+    assert!(lines[3].contains("CPU Fan"));
+    assert!(!lines[4].contains("CPU Fan"));
+
+    let mut replay = ReplayComponents::new(recording.as_slice(), Playback::Stepped).unwrap();
+    assert_eq!(replay.frame_count(), 7);
+    assert_eq!(replay.duration(), Duration::from_secs(60));
+    assert_eq!(replay.device_model_name(), "Mock Laptop");
+
+    for snapshot in &snapshots {
+        assert_eq!(replay.snapshot(), *snapshot);
+        replay.refresh().unwrap();
+    }
+    assert!(replay.is_finished());
+    assert_eq!(package(&replay), Temperature::from_celsius(110.0));
+    assert_eq!(replay.fan_components()[0].rpm(), Some(0.0));
+    assert!(replay.battery_components()[0].battery().is_some());
+
+    // Seeking lands on the last frame at or before the given time, in either direction.
+    replay.seek(Duration::from_secs(25));
+    assert_eq!(replay.elapsed(), Duration::from_secs(20));
+    assert_eq!(replay.snapshot(), snapshots[2]);
+    replay.seek(Duration::from_secs(45));
+    assert_eq!(replay.snapshot(), snapshots[4]);
+    replay.seek(Duration::ZERO);
+    assert_eq!(replay.snapshot(), snapshots[0]);
+
+    // Played back in real time, a recording starts at its first frame.
+    let mut replay = ReplayComponents::new(recording.as_slice(), Playback::Speed(1.0)).unwrap();
+    replay.refresh().unwrap();
+    assert_eq!(package(&replay), Temperature::from_celsius(45.0));
+}
+
+#[test]
+fn removed_components() {
+    let mut recorder = Recorder::new(Vec::new());
+    let mut snapshot = overheat().snapshot();
+    recorder.record(&snapshot).unwrap();
+    snapshot.components.retain(|c| c.label != "BAT0");
+    recorder.record(&snapshot).unwrap();
+
+    let recording = recorder.into_inner();
+    let mut replay = ReplayComponents::new(recording.as_slice(), Playback::Stepped).unwrap();
+    assert_eq!(replay.battery_components().len(), 1);
+    replay.refresh().unwrap();
+    assert!(replay.battery_components().is_empty());
+    assert_eq!(replay.thermal_components().len(), 1);
+}
+
+#[test]
+fn sensor_only_components() {
+    let mut recorder = Recorder::new(Vec::new());
+    let mut snapshot = overheat().snapshot();
+    let id = SensorId::new("mock:PSU");
+    snapshot.components.push(ComponentSnapshot {
+        id: id.clone(),
+        label: "PSU".to_string(),
+        component_type: ComponentType::Sensor,
+        temperatures: Vec::new(),
+        sensors: vec![SensorReading {
+            id: id.channel("in0"),
+            label: "12V".to_string(),
+            kind: SensorKind::Voltage,
+            value: 12.1,
+            min: None,
+            max: None,
+            status: ReadingStatus::Ok,
+        }],
+        battery: None,
+        percentage: None,
+        rpm: None,
+    });
+    recorder.record(&snapshot).unwrap();
+
+    let recording = recorder.into_inner();
+    let replay = ReplayComponents::new(recording.as_slice(), Playback::Stepped).unwrap();
+    assert_eq!(replay.sensor_components().len(), 1);
+    assert_eq!(replay.sensor_components()[0].label(), "PSU");
+    assert_eq!(
+        replay.component_by_id(&id.channel("in0")).unwrap().label(),
+        "PSU"
+    );
+    assert_eq!(replay.snapshot().components, snapshot.components);
+}
+
+#[test]
+fn invalid_recordings() {
+    let (recording, _) = record();
+    let recording = String::from_utf8(recording).unwrap();
+
+    let newer = recording.replacen("\"version\":1", "\"version\":2", 1);
+    let err = ReplayComponents::new(newer.as_bytes(), Playback::Stepped).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+    let err = ReplayComponents::new(&b"{\"hello\":1}\n"[..], Playback::Stepped).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err.to_string().contains("not a tmt recording"));
+
+    let truncated = &recording[..recording.len() - 20];
+    let err = ReplayComponents::new(truncated.as_bytes(), Playback::Stepped).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err.to_string().starts_with("line 8"));
+}
@@ -0,0 +1,52 @@
+//! Exercises the platform-independent [`Temperature`] type.
+
+use tmt_core::{Temperature, TemperatureUnit};
+
+#[test]
+fn unit_conversion() {
+    let body = Temperature::from_millidegrees(37_000);
+    assert_eq!(body.celsius(), 37.0);
+    assert!((body.fahrenheit() - 98.6).abs() < 1e-9);
+    assert!((body.kelvin() - 310.15).abs() < 1e-9);
+
+    // Conversions round-trip at hwmon's resolution of a thousandth of a degree.
+    for millidegrees in [-273_150, -40_000, -1, 0, 1, 48_125, 105_000] {
+        let temperature = Temperature::from_millidegrees(millidegrees);
+
+        for unit in [
+            TemperatureUnit::Celsius,
+            TemperatureUnit::Fahrenheit,
+            TemperatureUnit::Kelvin,
+        ] {
+            let value = temperature.to_unit(unit);
+            assert_eq!(Temperature::from_unit(value, unit), temperature);
+        }
+    }
+
+    assert_eq!(
+        Temperature::from_fahrenheit(-40.0),
+        Temperature::from_celsius(-40.0)
+    );
+    assert_eq!(Temperature::from_kelvin(0.0), Temperature::ABSOLUTE_ZERO);
+    assert!(Temperature::from_celsius(-5.0) < Temperature::default());
+}
+
+#[test]
+fn formatting() {
+    let temperature = Temperature::from_millidegrees(-12_345);
+
+    assert_eq!(format!("{:.1}", temperature), "-12.3 °C");
+    assert_eq!(
+        format!("{:.1}", temperature.display(TemperatureUnit::Fahrenheit)),
+        "9.8 °F"
+    );
+    assert_eq!(
+        format!("{:.2}", temperature.display(TemperatureUnit::Kelvin)),
+        "260.81 K"
+    );
+    assert_eq!(temperature.to_string(), "-12.345 °C");
+
+    assert_eq!("f".parse(), Ok(TemperatureUnit::Fahrenheit));
+    assert_eq!("Kelvin".parse(), Ok(TemperatureUnit::Kelvin));
+    assert!("R".parse::<TemperatureUnit>().is_err());
+}